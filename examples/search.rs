@@ -0,0 +1,85 @@
+//! Searches a Lavalink node for tracks, reading one identifier per line from
+//! standard input, such as `ytsearch:never gonna give you up`.
+//!
+//! Each line is searched as soon as it is entered, so the example can be used
+//! interactively or with identifiers piped into it.
+//!
+//! The node is configured with the `LAVALINK_HOST` (for example
+//! `http://127.0.0.1:2333`) and `LAVALINK_PASSWORD` environment variables.
+//!
+//! Run it with:
+//!
+//! ```sh
+//! $ cargo run --example search
+//! ```
+
+use futures::{sync::mpsc, Future, Stream};
+use lavalink::{decoder, rest::hyper::RestClient};
+use std::{
+    env,
+    io::{self, BufRead},
+    sync::Arc,
+    thread,
+};
+
+fn main() {
+    let host = env::var("LAVALINK_HOST")
+        .unwrap_or_else(|_| "http://127.0.0.1:2333".to_owned());
+    let password = env::var("LAVALINK_PASSWORD")
+        .expect("LAVALINK_PASSWORD must be set");
+    let client = Arc::new(
+        RestClient::new(host, password).expect("LAVALINK_HOST is invalid"),
+    );
+
+    // Reading standard input blocks, so it's done on its own thread, which
+    // sends each line to the searches as soon as it's read.
+    let (tx, identifiers) = mpsc::unbounded();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if tx.unbounded_send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let searches = identifiers.for_each(move |identifier| {
+        client.load_tracks(&identifier).then(move |result| {
+            match result {
+                Ok(load) => {
+                    println!("{}: {:?}", identifier, load.load_type);
+
+                    for track in load.tracks {
+                        // Decoding locally needs no round trip to the node.
+                        let source = decoder::decode_track_base64(&track.track)
+                            .map(|decoded| decoded.source)
+                            .unwrap_or_else(|_| "unknown".to_owned());
+
+                        println!(
+                            "  {} - {} ({:?}, {})",
+                            track.info.author,
+                            track.info.title,
+                            track.info.length(),
+                            source,
+                        );
+                    }
+                },
+                Err(why) => eprintln!("{}: failed to load: {}", identifier, why),
+            }
+
+            Ok(())
+        })
+    });
+
+    hyper::rt::run(searches);
+}
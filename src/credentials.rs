@@ -0,0 +1,216 @@
+//! Sources for the password used to authenticate with a node, so that it
+//! can be kept in a secret store rather than in the code that creates
+//! clients.
+//!
+//! A [`CredentialProvider`] is given to a REST client with its
+//! `set_credential_provider` method, which reads the password immediately.
+//! Calling the client's `refresh_credentials` method reads it again, such as
+//! when the process receives `SIGHUP` after the password was rotated.
+//!
+//! # Examples
+//!
+//! Read the password from a file mounted by a secret manager:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "hyper")]
+//! # fn main() -> lavalink::Result<()> {
+//! use lavalink::credentials::FileCredentials;
+//! use lavalink::rest::hyper::RestClient;
+//!
+//! let mut client = RestClient::new("127.0.0.1:2333", "")?;
+//! client.set_credential_provider(
+//!     FileCredentials::new("/run/secrets/lavalink_password"),
+//! )?;
+//!
+//! // later, after the secret was rotated
+//! client.refresh_credentials()?;
+//! #     Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "hyper"))]
+//! # fn main() {}
+//! ```
+//!
+//! [`CredentialProvider`]: trait.CredentialProvider.html
+
+use crate::{Result, Secret};
+use std::env::{self, VarError};
+use std::fs;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::path::{Path, PathBuf};
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+/// A source of the password used to authenticate with a node.
+///
+/// This is implemented for [`Secret`], which always provides the same
+/// password, and for closures returning a `Result<Secret>`, such as one that
+/// requests the password from a vault.
+///
+/// [`Secret`]: ../struct.Secret.html
+pub trait CredentialProvider: Send + Sync {
+    /// Reads the current password.
+    fn password(&self) -> Result<Secret>;
+}
+
+impl CredentialProvider for Secret {
+    fn password(&self) -> Result<Secret> {
+        Ok(self.clone())
+    }
+}
+
+impl<F: Fn() -> Result<Secret> + Send + Sync> CredentialProvider for F {
+    fn password(&self) -> Result<Secret> {
+        self()
+    }
+}
+
+/// A provider that reads the password from an environment variable.
+#[derive(Clone, Debug)]
+pub struct EnvCredentials {
+    name: String,
+}
+
+impl EnvCredentials {
+    /// Creates a provider that reads the environment variable with a name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+        }
+    }
+}
+
+impl CredentialProvider for EnvCredentials {
+    /// Reads the password from the environment variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the variable isn't set or isn't valid
+    /// unicode.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    fn password(&self) -> Result<Secret> {
+        match env::var(&self.name) {
+            Ok(value) => Ok(Secret::from(value)),
+            Err(VarError::NotPresent) => Err(IoError::new(
+                IoErrorKind::NotFound,
+                format!("environment variable {} is not set", self.name),
+            ).into()),
+            Err(VarError::NotUnicode(_)) => Err(IoError::new(
+                IoErrorKind::InvalidData,
+                format!("environment variable {} is not unicode", self.name),
+            ).into()),
+        }
+    }
+}
+
+/// A provider that reads the password from a file.
+///
+/// A single trailing newline is removed from the contents of the file, as
+/// most editors and secret managers write one.
+#[derive(Clone, Debug)]
+pub struct FileCredentials {
+    path: PathBuf,
+}
+
+impl FileCredentials {
+    /// Creates a provider that reads the file at a path.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+        }
+    }
+}
+
+impl CredentialProvider for FileCredentials {
+    /// Reads the password from the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file couldn't be read.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    fn password(&self) -> Result<Secret> {
+        let mut contents = fs::read(&self.path)?;
+
+        if contents.last() == Some(&b'\n') {
+            contents.pop();
+
+            if contents.last() == Some(&b'\r') {
+                contents.pop();
+            }
+        }
+
+        Ok(Secret::new(contents))
+    }
+}
+
+/// The credential provider set on a client, if any.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+#[derive(Default)]
+pub(crate) struct ProviderSlot(Option<Box<dyn CredentialProvider>>);
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+impl ProviderSlot {
+    pub(crate) fn set(&mut self, provider: Box<dyn CredentialProvider>) {
+        self.0 = Some(provider);
+    }
+
+    /// Reads the password from the provider, or returns `None` if there is
+    /// none.
+    pub(crate) fn password(&self) -> Option<Result<Secret>> {
+        self.0.as_ref().map(|provider| provider.password())
+    }
+}
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+impl Debug for ProviderSlot {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.0 {
+            Some(_) => f.write_str("Some(CredentialProvider)"),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Secret;
+    use std::env;
+    use std::fs;
+    use super::{CredentialProvider, EnvCredentials, FileCredentials};
+
+    #[test]
+    fn test_env_credentials() {
+        let name = "LAVALINK_TEST_ENV_CREDENTIALS";
+        let provider = EnvCredentials::new(name);
+        assert!(provider.password().is_err());
+
+        env::set_var(name, "youshallnotpass");
+        assert_eq!(provider.password().unwrap().expose(), b"youshallnotpass");
+        env::remove_var(name);
+    }
+
+    #[test]
+    fn test_file_credentials() {
+        let path = env::temp_dir().join(format!(
+            "lavalink-test-credentials-{}",
+            std::process::id(),
+        ));
+        let provider = FileCredentials::new(&path);
+
+        fs::write(&path, "youshallnotpass\r\n").unwrap();
+        assert_eq!(provider.password().unwrap().expose(), b"youshallnotpass");
+
+        // the file is read again on every call
+        fs::write(&path, "rotated\n\n").unwrap();
+        assert_eq!(provider.password().unwrap().expose(), b"rotated\n");
+
+        fs::remove_file(&path).unwrap();
+        assert!(provider.password().is_err());
+
+        let closure = || Ok(Secret::new("from a vault"));
+        assert_eq!(closure.password().unwrap().expose(), b"from a vault");
+    }
+}
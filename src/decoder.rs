@@ -1,29 +1,105 @@
 //! Functions for decoding a track.
 
 use byteorder::{BE, ReadBytesExt};
-use crate::Result;
+use crate::rest::LoadedTrack;
+use crate::sanitize::{sanitize, SanitizePolicy};
+use crate::{Error, Result};
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::io::{Cursor, Read};
+use std::result::Result as StdResult;
+use std::str;
+use std::time::Duration;
 
-const TRACK_INFO_VERSIONED: i32 = 1;
-
-macro_rules! move_cursor {
-    ($c:ident,$s:tt) => {
-        let pos = $c.position();
-        $c.set_position(pos + $s);
-    };
-}
+const TRACK_INFO_VERSIONED: u32 = 1;
 
 fn read_string(cursor: &mut Cursor<Vec<u8>>) -> Result<String> {
     let size = cursor.read_u16::<BE>()?;
     let mut buf = vec![0u8; size as usize];
     cursor.read_exact(&mut buf)?;
 
-    let string = String::from_utf8_lossy(&buf);
-    Ok(string.to_owned().to_string())
+    Ok(decode_modified_utf8(&buf))
+}
+
+/// Decodes a string written by Java's `DataOutput.writeUTF`, which lavaplayer
+/// uses for the strings of a blob.
+///
+/// This "modified UTF-8" encodes characters outside the Basic Multilingual
+/// Plane, such as emoji, as two three-byte surrogates, and the null character
+/// as two bytes. Invalid sequences are replaced with U+FFFD.
+fn decode_modified_utf8(bytes: &[u8]) -> String {
+    // neither surrogates nor the two-byte null character are valid UTF-8, so
+    // a string that is valid UTF-8 reads the same in both encodings
+    if let Ok(text) = str::from_utf8(bytes) {
+        return text.to_owned();
+    }
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+
+    while let Some(&first) = rest.first() {
+        // the length of the sequence, and the bits of the first byte that
+        // are part of the code
+        let (len, mask) = match first {
+            0x00..=0x7F => (1, 0x7F),
+            0xC0..=0xDF => (2, 0x1F),
+            0xE0..=0xEF => (3, 0x0F),
+            0xF0..=0xF4 => (4, 0x07),
+            _ => (0, 0),
+        };
+        let sequence = rest.get(..len).filter(|sequence| {
+            len > 0 && sequence[1..].iter().all(|byte| byte & 0xC0 == 0x80)
+        });
+
+        let sequence = match sequence {
+            Some(sequence) => sequence,
+            None => {
+                units.push(0xFFFD);
+                rest = &rest[1..];
+
+                continue;
+            },
+        };
+
+        let code = sequence[1..].iter().fold(
+            u32::from(first & mask),
+            |code, byte| code << 6 | u32::from(byte & 0x3F),
+        );
+
+        // four-byte sequences aren't written by Java, but are read like
+        // UTF-8 in case a blob was encoded elsewhere
+        match std::char::from_u32(code).filter(|_| len == 4) {
+            Some(c) => {
+                let mut buf = [0; 2];
+                units.extend_from_slice(c.encode_utf16(&mut buf));
+            },
+            None if code <= 0xFFFF => units.push(code as u16),
+            None => units.push(0xFFFD),
+        }
+
+        rest = &rest[len..];
+    }
+
+    String::from_utf16_lossy(&units)
+}
+
+fn read_nullable_string(cursor: &mut Cursor<Vec<u8>>) -> Result<Option<String>> {
+    if cursor.read_u8()? == 0 {
+        return Ok(None);
+    }
+
+    read_string(cursor).map(Some)
 }
 
 /// Holds decoded track information from a lavaplayer track blob
-#[derive(Debug)]
+///
+/// The serde representation is an object with the same field names as this
+/// struct, where `url` is `null` if the track has no URL, and `artwork_url`
+/// and `isrc` are omitted if the track doesn't have them. This layout is part
+/// of the public API, so serialized tracks can be cached and deserialized by
+/// later versions of the library.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct DecodedTrack {
     /// The version of the track.
     pub version: u8,
@@ -31,7 +107,7 @@ pub struct DecodedTrack {
     pub title: String,
     /// The author of the track.
     pub author: String,
-    /// The length of the track.
+    /// The length of the track in milliseconds.
     pub length: u64,
     /// The unique identifier for the track.
     pub identifier: String,
@@ -41,6 +117,37 @@ pub struct DecodedTrack {
     pub url: Option<String>,
     /// The source of the track.
     pub source: String,
+    /// The URL of the track's artwork, such as its album art.
+    ///
+    /// This is only present in version 3 blobs, which are created by Lavalink
+    /// v4 nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artwork_url: Option<String>,
+    /// The International Standard Recording Code of the track.
+    ///
+    /// This is only present in version 3 blobs, which are created by Lavalink
+    /// v4 nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isrc: Option<String>,
+}
+
+impl DecodedTrack {
+    /// Returns the title of the track, cleaned up for display according to a
+    /// policy.
+    pub fn sanitized_title(&self, policy: &SanitizePolicy) -> String {
+        sanitize(&self.title, policy)
+    }
+
+    /// Returns the author of the track, cleaned up for display according to
+    /// a policy.
+    pub fn sanitized_author(&self, policy: &SanitizePolicy) -> String {
+        sanitize(&self.author, policy)
+    }
+
+    /// Returns the length of the track.
+    pub fn length(&self) -> Duration {
+        Duration::from_millis(self.length)
+    }
 }
 
 /// Decodes a binary lavaplayer track blob
@@ -52,42 +159,40 @@ pub fn decode_track(input: impl Into<Vec<u8>>) -> Result<DecodedTrack> {
 fn _decode_track(input: Vec<u8>) -> Result<DecodedTrack> {
     let mut cursor = Cursor::new(input);
 
-    let value = cursor.read_u8()?;
-    let flags = ((i64::from(value) & 0xC000_0000) >> 30) as i32;
-
-    // gets the message size (we dont care)
-    // let size = value as i32 & 0x3FFFFFFF;
-
-    move_cursor!(cursor, 2);
+    // the message size is in the lower 30 bits, which we don't need
+    let header = cursor.read_u32::<BE>()?;
+    let flags = header >> 30;
 
     let version = match flags & TRACK_INFO_VERSIONED {
         0 => 1,
         _ => cursor.read_u8()?,
     };
 
-    move_cursor!(cursor, 2); // dont care
-
     let title = read_string(&mut cursor)?;
     let author = read_string(&mut cursor)?;
     let length = cursor.read_u64::<BE>()?;
     let identifier = read_string(&mut cursor)?;
-
     let stream = cursor.read_u8()? == 1;
-    let has_url = cursor.read_u8()? == 1;
 
-    let url = if has_url {
-        Some(read_string(&mut cursor)?)
+    let url = if version >= 2 {
+        read_nullable_string(&mut cursor)?
     } else {
-        let size = u64::from(cursor.read_u8()?);
-        move_cursor!(cursor, size);
         None
     };
 
+    let (artwork_url, isrc) = if version >= 3 {
+        (read_nullable_string(&mut cursor)?, read_nullable_string(&mut cursor)?)
+    } else {
+        (None, None)
+    };
+
     let source = read_string(&mut cursor)?;
 
     Ok(DecodedTrack {
+        artwork_url,
         author,
         identifier,
+        isrc,
         length,
         source,
         stream,
@@ -108,6 +213,292 @@ fn _decode_track_base64(input: &str) -> Result<DecodedTrack> {
     decode_track(::base64::decode(input)?)
 }
 
+/// Decodes a lavaplayer track blob given in any of the representations it is
+/// commonly pasted in, such as when debugging a track that won't decode.
+///
+/// The input may be:
+///
+/// - base64, as sent by nodes;
+/// - hexadecimal, optionally prefixed with `0x` and separated by whitespace;
+/// - a string of escaped bytes, such as `\x40\x00` or `\u0040\u0000`, as
+///   printed by debuggers and JSON encoders.
+///
+/// Surrounding quotes are ignored. Each representation that the input could
+/// be is tried in turn, and the first blob that decodes is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> lavalink::Result<()> {
+/// use lavalink::decoder;
+///
+/// let base64 = "QAAAIwIAAXQAAWEAAAAAAAAD6AACaWQAAAAEaHR0cAAAAAAAAAAA";
+/// let hex = "40000023 02000174 00016100 00000000 0003e800 02696400 \
+///            00000468 74747000 00000000 000000";
+///
+/// let track = decoder::decode_flexible(hex)?;
+/// assert_eq!(track.identifier, "id");
+/// assert_eq!(track, decoder::decode_flexible(base64)?);
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns the error from decoding the input as base64 if no representation
+/// could be decoded.
+#[inline]
+pub fn decode_flexible(input: impl AsRef<str>) -> Result<DecodedTrack> {
+    _decode_flexible(input.as_ref())
+}
+
+fn _decode_flexible(input: &str) -> Result<DecodedTrack> {
+    let input = input.trim();
+    let input = input
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(input);
+
+    let candidates = [parse_hex(input), parse_escaped(input)];
+
+    for blob in candidates.iter().flatten() {
+        if let Ok(track) = decode_track(blob.clone()) {
+            return Ok(track);
+        }
+    }
+
+    let compact = input.split_whitespace().collect::<String>();
+
+    _decode_track_base64(&compact)
+}
+
+fn parse_hex(input: &str) -> Option<Vec<u8>> {
+    let input = input.strip_prefix("0x").unwrap_or(input);
+    let digits = input
+        .split_whitespace()
+        .map(|word| word.strip_prefix("0x").unwrap_or(word))
+        .collect::<String>();
+
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn parse_escaped(input: &str) -> Option<Vec<u8>> {
+    if !input.contains('\\') {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+
+            continue;
+        }
+
+        let byte = match chars.next()? {
+            'x' => u8::from_str_radix(&take(&mut chars, 2)?, 16).ok()?,
+            // JSON encoders escape bytes that aren't valid UTF-8 as code
+            // points, so each is a single byte
+            'u' => {
+                let code = u16::from_str_radix(&take(&mut chars, 4)?, 16).ok()?;
+
+                u8::try_from(code).ok()?
+            },
+            '0' => 0,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            '\\' => b'\\',
+            '"' => b'"',
+            '\'' => b'\'',
+            '/' => b'/',
+            _ => return None,
+        };
+
+        bytes.push(byte);
+    }
+
+    Some(bytes)
+}
+
+fn take(
+    chars: &mut impl Iterator<Item = char>,
+    count: usize,
+) -> Option<String> {
+    let taken = chars.take(count).collect::<String>();
+
+    if taken.len() == count {
+        Some(taken)
+    } else {
+        None
+    }
+}
+
+/// Decodes a batch of binary lavaplayer track blobs.
+///
+/// Each blob is decoded independently, so a blob that fails to decode does
+/// not affect the others. The results are in the same order as the input.
+pub fn decode_many(
+    blobs: impl IntoIterator<Item = impl Into<Vec<u8>>>,
+) -> Vec<Result<DecodedTrack>> {
+    blobs.into_iter().map(decode_track).collect()
+}
+
+/// Checks that a loaded track's blob describes the same track as the
+/// information the node sent with it.
+///
+/// The blob is decoded locally and its title, author, length and identifier
+/// are compared. A mismatch points to a node and library that disagree on
+/// the blob format, or to a corrupted cache entry.
+///
+/// # Examples
+///
+/// Audit the tracks of a load:
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use lavalink::decoder;
+/// use lavalink::rest::Load;
+///
+/// # let load = serde_json::from_value::<Load>(serde_json::json!({
+/// #     "loadType": "TRACK_LOADED",
+/// #     "playlistInfo": {},
+/// #     "tracks": [{
+/// #         "track": "QAAAjQIAJVJpY2sgQXN0bGV5IC0gTmV2ZXIgR29ubmEgR2l2ZSBZb3UgVXAADlJpY2tBc3RsZXlWRVZPAAAAAAADPCAAC2RRdzR3OVdnWGNRAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9ZFF3NHc5V2dYY1EAB3lvdXR1YmUAAAAAAAAAAA==",
+/// #         "info": {
+/// #             "identifier": "dQw4w9WgXcQ",
+/// #             "isSeekable": true,
+/// #             "author": "RickAstleyVEVO",
+/// #             "length": 212000,
+/// #             "isStream": false,
+/// #             "position": 0,
+/// #             "title": "Rick Astley - Never Gonna Give You Up",
+/// #             "uri": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+/// #         }
+/// #     }]
+/// # }))?;
+/// for track in &load.tracks {
+///     if let Err(mismatch) = decoder::validate_consistency(track) {
+///         eprintln!("Inconsistent track {}: {}", track.info.identifier, mismatch);
+///     }
+/// }
+/// # assert!(decoder::validate_consistency(&load.tracks[0]).is_ok());
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Mismatch::Undecodable`] if the blob could not be decoded, and
+/// [`Mismatch::Fields`] if any of the compared fields differ.
+///
+/// [`Mismatch::Fields`]: enum.Mismatch.html#variant.Fields
+/// [`Mismatch::Undecodable`]: enum.Mismatch.html#variant.Undecodable
+pub fn validate_consistency(
+    loaded: &LoadedTrack,
+) -> StdResult<(), Mismatch> {
+    let decoded = decode_track_base64(&loaded.track)
+        .map_err(Mismatch::Undecodable)?;
+    let info = &loaded.info;
+
+    let fields = [
+        ("title", decoded.title.clone(), info.title.clone()),
+        ("author", decoded.author.clone(), info.author.clone()),
+        ("length", decoded.length.to_string(), info.length.to_string()),
+        ("identifier", decoded.identifier.clone(), info.identifier.clone()),
+    ];
+
+    let mismatched = fields.iter()
+        .filter(|(_, decoded, loaded)| decoded != loaded)
+        .map(|(field, decoded, loaded)| FieldMismatch {
+            field,
+            decoded: decoded.clone(),
+            loaded: loaded.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    if mismatched.is_empty() {
+        Ok(())
+    } else {
+        Err(Mismatch::Fields(mismatched))
+    }
+}
+
+/// The reason a loaded track failed [`validate_consistency`].
+///
+/// [`validate_consistency`]: fn.validate_consistency.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Mismatch {
+    /// The fields of the decoded blob that differ from the node's
+    /// information.
+    Fields(Vec<FieldMismatch>),
+    /// The blob could not be decoded.
+    Undecodable(Error),
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Mismatch::Fields(fields) => {
+                f.write_str("Decoded track differs in ")?;
+
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+
+                    write!(
+                        f,
+                        "{} ({:?} != {:?})",
+                        field.field,
+                        field.decoded,
+                        field.loaded,
+                    )?;
+                }
+
+                Ok(())
+            },
+            Mismatch::Undecodable(why) => {
+                write!(f, "Track could not be decoded: {}", why)
+            },
+        }
+    }
+}
+
+impl StdError for Mismatch {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Mismatch::Fields(_) => None,
+            Mismatch::Undecodable(why) => Some(why),
+        }
+    }
+}
+
+/// A field of a decoded track that differs from the node's information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct FieldMismatch {
+    /// The name of the field, such as `title`.
+    pub field: &'static str,
+    /// The value of the field in the decoded blob.
+    pub decoded: String,
+    /// The value of the field in the node's information.
+    pub loaded: String,
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -116,6 +507,180 @@ mod tests {
 VSIFNPTUVUSElORyBOSUNFIO2gve2ypu2gve24nAAJTmlUcmlzIFR2AAAAAAAM/DgACzlFRFNDX0Rqb\
 zFnAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9OUVEU0NfRGpvMWcAB3lvdXR1YmUA\
 AAAAAAAAAA==";
-        super::decode_track_base64(track).unwrap();
+        let track = super::decode_track_base64(track).unwrap();
+        // the emoji are written as surrogate pairs
+        assert_eq!(
+            track.title,
+            "SHE WAS MAD AT ME, SO I HAD TO BUY HER SOMETHING NICE \u{1f4a6}\u{1f61c}",
+        );
+    }
+
+    #[test]
+    fn test_modified_utf8() {
+        use super::decode_modified_utf8;
+
+        assert_eq!(decode_modified_utf8("caf\u{e9}".as_bytes()), "caf\u{e9}");
+        assert_eq!(decode_modified_utf8(b"a\xc0\x80b"), "a\u{0}b");
+        assert_eq!(
+            decode_modified_utf8(b"\xed\xa0\xbd\xed\xb8\x9c \xf0\x9f\x98\x9c"),
+            "\u{1f61c} \u{1f61c}",
+        );
+        // unpaired surrogates and truncated sequences are replaced
+        assert_eq!(decode_modified_utf8(b"\xed\xa0\xbd!"), "\u{fffd}!");
+        assert_eq!(decode_modified_utf8(b"\xe2\x82"), "\u{fffd}\u{fffd}");
+    }
+
+    #[test]
+    fn test_serde_layout() {
+        let track = super::DecodedTrack {
+            version: 2,
+            title: "foo".to_owned(),
+            author: "bar".to_owned(),
+            length: 60000,
+            identifier: "baz".to_owned(),
+            stream: false,
+            url: None,
+            source: "http".to_owned(),
+            artwork_url: None,
+            isrc: None,
+        };
+
+        let json = r#"{"version":2,"title":"foo","author":"bar","length":60000,"identifier":"baz","stream":false,"url":null,"source":"http"}"#;
+        assert_eq!(::serde_json::to_string(&track).unwrap(), json);
+        assert_eq!(
+            ::serde_json::from_str::<super::DecodedTrack>(json).unwrap(),
+            track,
+        );
+    }
+
+    #[test]
+    fn test_decode_many() {
+        let track = ::base64::decode("QAAApQIAQlNIRSBXQVMgTUFEIEFUIE1FLCBTTyBJIEhBRCBUTyBCVVkgSE\
+VSIFNPTUVUSElORyBOSUNFIO2gve2ypu2gve24nAAJTmlUcmlzIFR2AAAAAAAM/DgACzlFRFNDX0Rqb\
+zFnAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9OUVEU0NfRGpvMWcAB3lvdXR1YmUA\
+AAAAAAAAAA==").unwrap();
+
+        let decoded = super::decode_many(vec![track, vec![0u8; 3]]);
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].as_ref().unwrap().identifier, "9EDSC_Djo1g");
+        assert!(decoded[1].is_err());
+    }
+
+    #[test]
+    fn test_version_3() {
+        fn string(blob: &mut Vec<u8>, value: &str) {
+            blob.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            blob.extend_from_slice(value.as_bytes());
+        }
+
+        let mut body = vec![3];
+        string(&mut body, "title");
+        string(&mut body, "author");
+        body.extend_from_slice(&212_000u64.to_be_bytes());
+        string(&mut body, "dQw4w9WgXcQ");
+        body.push(0);
+        body.push(1);
+        string(&mut body, "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        body.push(1);
+        string(&mut body, "https://i.ytimg.com/vi/dQw4w9WgXcQ/mqdefault.jpg");
+        body.push(0);
+        string(&mut body, "youtube");
+        body.extend_from_slice(&0u64.to_be_bytes());
+
+        let mut blob = (body.len() as u32 | 1 << 30).to_be_bytes().to_vec();
+        blob.extend(body);
+
+        let track = super::decode_track(blob).unwrap();
+        assert_eq!(track.version, 3);
+        assert_eq!(track.identifier, "dQw4w9WgXcQ");
+        assert_eq!(
+            track.artwork_url.as_ref().unwrap(),
+            "https://i.ytimg.com/vi/dQw4w9WgXcQ/mqdefault.jpg",
+        );
+        assert!(track.isrc.is_none());
+        assert_eq!(track.source, "youtube");
+    }
+
+    #[test]
+    fn test_decode_flexible() {
+        let base64 = "QAAApQIAQlNIRSBXQVMgTUFEIEFUIE1FLCBTTyBJIEhBRCBUTyBCVVkgSE\
+VSIFNPTUVUSElORyBOSUNFIO2gve2ypu2gve24nAAJTmlUcmlzIFR2AAAAAAAM/DgACzlFRFNDX0Rqb\
+zFnAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9OUVEU0NfRGpvMWcAB3lvdXR1YmUA\
+AAAAAAAAAA==";
+        let blob = ::base64::decode(base64).unwrap();
+        let expected = super::decode_track(blob.clone()).unwrap();
+
+        let encode = |f: fn(&u8) -> String| blob.iter().map(f).collect::<String>();
+        let hex = encode(|b| format!("{:02x} ", b));
+        let escaped = encode(|b| format!("\\x{:02x}", b));
+        let json = encode(|b| format!("\\u{:04x}", b));
+
+        for input in &[
+            format!("  {}\n", base64),
+            format!("\"{}\"", base64),
+            hex.clone(),
+            format!("0x{}", hex.replace(' ', "")),
+            escaped,
+            json,
+        ] {
+            assert_eq!(super::decode_flexible(input).unwrap(), expected);
+        }
+
+        assert!(super::decode_flexible("not a track").is_err());
+        assert!(super::decode_flexible("\\x4").is_err());
+    }
+
+    #[test]
+    fn test_validate_consistency() {
+        use crate::rest::LoadedTrack;
+        use super::Mismatch;
+
+        // title "t", author "a", length 1000 and identifier "id"
+        let mut track = ::serde_json::from_value::<LoadedTrack>(::serde_json::json!({
+            "track": "QAAAIwIAAXQAAWEAAAAAAAAD6AACaWQAAAAEaHR0cAAAAAAAAAAA",
+            "info": {
+                "title": "t",
+                "author": "a",
+                "length": 1000,
+                "identifier": "id",
+                "uri": "",
+                "isStream": false,
+                "isSeekable": true,
+                "position": 0
+            }
+        })).unwrap();
+        assert!(super::validate_consistency(&track).is_ok());
+
+        track.info.length = 2000;
+        match super::validate_consistency(&track) {
+            Err(Mismatch::Fields(fields)) => {
+                assert_eq!(fields.len(), 1);
+                assert_eq!(fields[0].field, "length");
+                assert_eq!(fields[0].decoded, "1000");
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // titles with emoji decode the same as the node's information
+        let emoji = ::serde_json::from_value::<LoadedTrack>(::serde_json::json!({
+            "track": "QAAApQIAQlNIRSBXQVMgTUFEIEFUIE1FLCBTTyBJIEhBRCBUTyBCVVkgSEVSIFNPTUVUSElORyBOSUNFIO2gve2ypu2gve24nAAJTmlUcmlzIFR2AAAAAAAM/DgACzlFRFNDX0RqbzFnAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9OUVEU0NfRGpvMWcAB3lvdXR1YmUAAAAAAAAAAA==",
+            "info": {
+                "title": "SHE WAS MAD AT ME, SO I HAD TO BUY HER SOMETHING NICE \u{1f4a6}\u{1f61c}",
+                "author": "NiTris Tv",
+                "length": 851000,
+                "identifier": "9EDSC_Djo1g",
+                "uri": "https://www.youtube.com/watch?v=9EDSC_Djo1g",
+                "isStream": false,
+                "isSeekable": true,
+                "position": 0
+            }
+        })).unwrap();
+        assert!(super::validate_consistency(&emoji).is_ok());
+
+        track.track = "QAAA".to_owned();
+        match super::validate_consistency(&track) {
+            Err(Mismatch::Undecodable(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 }
@@ -1,11 +1,15 @@
 use serde_json::Error as JsonError;
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::io::Error as IoError;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 use std::result::Result as StdResult;
 use std::sync::mpsc::SendError;
 use std::string::FromUtf8Error;
+use std::sync::Arc;
 use base64::DecodeError;
+use crate::model::MalformedMessage;
+use crate::opcodes::UnknownOpcodeError;
+use crate::rest::{IdentifierError, StatusError};
 
 #[cfg(feature = "http")]
 use http::{
@@ -29,6 +33,7 @@ pub type Result<T> = StdResult<T, Error>;
 
 /// Common error type used throughout the library's return types.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// An error from the `http` crate.
     #[cfg(feature = "http")]
@@ -36,22 +41,74 @@ pub enum Error {
     /// An error from the `hyper` crate.
     #[cfg(feature = "hyper")]
     Hyper(HyperError),
+    /// A guild ID was not a valid snowflake.
+    ///
+    /// This contains the invalid value.
+    InvalidGuildId(String),
     /// An invalid header value while setting a reqwest header.
     #[cfg(feature = "reqwest")]
     InvalidHeaderValue(InvalidHeaderValue),
+    /// A node's host could not be parsed.
+    ///
+    /// Hosts must be either `host:port` or an `http://` or `https://` URL.
+    /// This contains the invalid host.
+    InvalidHost(String),
+    /// An identifier was rejected before loading it, such as for being too
+    /// long.
+    InvalidIdentifier(IdentifierError),
+    /// A node's version could not be parsed.
+    ///
+    /// This contains the invalid version.
+    InvalidNodeVersion(String),
     /// An error from the `std::io` module.
     Io(IoError),
     /// An error from the `serde_json` crate.
     Json(JsonError),
+    /// A message from the node could not be parsed.
+    ///
+    /// This describes the message, along with the JSON error.
+    MalformedMessage(Box<MalformedMessage>),
+    /// A request to a Lavalink v4 node needed a session ID, but none was set.
+    ///
+    /// The session ID is received in the node's [`Ready`] message.
+    ///
+    /// [`Ready`]: model/struct.Ready.html
+    MissingSessionId,
     /// A player already exists for the guild.
     PlayerAlreadyExists,
     /// An error from the `reqwest` crate.
     #[cfg(feature = "reqwest")]
     Reqwest(ReqwestError),
+    /// An extra header was given that the library sets itself, such as
+    /// `Authorization`.
+    ///
+    /// This contains the name of the header.
+    ReservedHeader(String),
     /// An error occurred sending a WebSocket message to an mpsc Receiver.
     ///
     /// This is the `Display` implementation of the error.
     Send(String),
+    /// A request that was shared between several callers failed.
+    ///
+    /// This contains the error of the request, which each caller receives.
+    Shared(Arc<Error>),
+    /// A node responded to a request with an unsuccessful HTTP status.
+    Status(StatusError),
+    /// A message from the node had an event type that isn't known, while
+    /// parsing in [`ParseMode::Strict`].
+    ///
+    /// This contains the unknown event type.
+    ///
+    /// [`ParseMode::Strict`]: model/enum.ParseMode.html#variant.Strict
+    UnknownEvent(String),
+    /// A message from the node had an opcode that isn't known, while parsing
+    /// in [`ParseMode::Strict`].
+    ///
+    /// This contains the unknown opcode, which is empty if the message had
+    /// none.
+    ///
+    /// [`ParseMode::Strict`]: model/enum.ParseMode.html#variant.Strict
+    UnknownOpcode(String),
     /// An error from the `hyper` crate while parsing a URI.
     #[cfg(feature = "http")]
     Uri(InvalidUri),
@@ -61,35 +118,199 @@ pub enum Error {
     Base64Error(DecodeError),
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        f.write_str(self.description())
+/// A broad classification of an [`Error`], to decide how to handle it without
+/// matching on every variant.
+///
+/// [`Error`]: enum.Error.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// The library was configured incorrectly, such as with an invalid host
+    /// or password, or a missing session ID.
+    ///
+    /// Retrying won't help until the configuration is fixed.
+    Configuration,
+    /// A value passed to the library was invalid, such as a guild ID.
+    InvalidInput,
+    /// The node sent something the library couldn't understand.
+    ///
+    /// This usually means the node runs a version that isn't supported.
+    Protocol,
+    /// Communicating with the node failed, such as from a dropped connection
+    /// or a timeout.
+    ///
+    /// Use [`Error::is_retryable`] to check whether trying again may work.
+    ///
+    /// [`Error::is_retryable`]: enum.Error.html#method.is_retryable
+    Transport,
+}
+
+impl Error {
+    /// Returns the kind of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::{Error, ErrorKind};
+    ///
+    /// let error = Error::InvalidHost("ws://127.0.0.1".to_owned());
+    /// assert_eq!(error.kind(), ErrorKind::Configuration);
+    /// assert!(!error.is_retryable());
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            #[cfg(feature = "http")]
+            Error::Http(_) => ErrorKind::Configuration,
+            #[cfg(feature = "hyper")]
+            Error::Hyper(ref inner) if inner.is_user() => ErrorKind::Configuration,
+            #[cfg(feature = "hyper")]
+            Error::Hyper(ref inner) if inner.is_parse() => ErrorKind::Protocol,
+            #[cfg(feature = "hyper")]
+            Error::Hyper(_) => ErrorKind::Transport,
+            Error::InvalidGuildId(_) => ErrorKind::InvalidInput,
+            #[cfg(feature = "reqwest")]
+            Error::InvalidHeaderValue(_) => ErrorKind::Configuration,
+            Error::InvalidHost(_) => ErrorKind::Configuration,
+            Error::InvalidIdentifier(_) => ErrorKind::InvalidInput,
+            Error::InvalidNodeVersion(_) => ErrorKind::Protocol,
+            Error::Io(_) => ErrorKind::Transport,
+            Error::Json(ref inner) if inner.is_io() => ErrorKind::Transport,
+            Error::Json(_) => ErrorKind::Protocol,
+            Error::MalformedMessage(_) => ErrorKind::Protocol,
+            Error::MissingSessionId => ErrorKind::Configuration,
+            Error::PlayerAlreadyExists => ErrorKind::InvalidInput,
+            #[cfg(feature = "reqwest")]
+            Error::Reqwest(ref inner) if inner.is_serialization() => {
+                ErrorKind::Protocol
+            },
+            #[cfg(feature = "reqwest")]
+            Error::Reqwest(ref inner) if inner.is_client_error() => {
+                ErrorKind::Configuration
+            },
+            #[cfg(feature = "reqwest")]
+            Error::Reqwest(_) => ErrorKind::Transport,
+            Error::ReservedHeader(_) => ErrorKind::Configuration,
+            Error::Send(_) => ErrorKind::Transport,
+            Error::Shared(ref inner) => inner.kind(),
+            Error::Status(ref inner) => match inner.status {
+                401 | 403 => ErrorKind::Configuration,
+                408 | 429 | 500..=599 => ErrorKind::Transport,
+                _ => ErrorKind::InvalidInput,
+            },
+            Error::UnknownEvent(_) | Error::UnknownOpcode(_) => {
+                ErrorKind::Protocol
+            },
+            #[cfg(feature = "http")]
+            Error::Uri(_) => ErrorKind::Configuration,
+            Error::ParseUtf8(_) | Error::Base64Error(_) => ErrorKind::Protocol,
+        }
+    }
+
+    /// Whether the operation that failed may succeed if it is tried again
+    /// later.
+    ///
+    /// Only [`ErrorKind::Transport`] errors can be retryable, and of those,
+    /// only ones that are likely temporary, such as timeouts, refused or
+    /// reset connections, and [`Error::Status`] errors for server errors and
+    /// rate limits.
+    ///
+    /// [`Error::Status`]: enum.Error.html#variant.Status
+    /// [`ErrorKind::Transport`]: enum.ErrorKind.html#variant.Transport
+    pub fn is_retryable(&self) -> bool {
+        if self.kind() != ErrorKind::Transport {
+            return false;
+        }
+
+        match *self {
+            // other hyper errors, such as unexpected messages, are likely to
+            // happen again
+            #[cfg(feature = "hyper")]
+            Error::Hyper(ref inner) => {
+                inner.is_connect()
+                    || inner.is_incomplete_message()
+                    || inner
+                        .source()
+                        .and_then(|cause| cause.downcast_ref::<IoError>())
+                        .map_or(false, |cause| is_retryable_io(cause.kind()))
+            },
+            Error::Io(ref inner) => is_retryable_io(inner.kind()),
+            // the receiver was dropped, so every later send fails as well
+            Error::Send(_) => false,
+            Error::Shared(ref inner) => inner.is_retryable(),
+            #[cfg(feature = "reqwest")]
+            Error::Reqwest(ref inner) => {
+                inner.is_timeout() || inner.is_http() || inner.is_server_error()
+            },
+            _ => true,
+        }
     }
 }
 
-impl StdError for Error {
-    fn description(&self) -> &str {
+fn is_retryable_io(kind: IoErrorKind) -> bool {
+    matches!(
+        kind,
+        IoErrorKind::BrokenPipe
+            | IoErrorKind::ConnectionAborted
+            | IoErrorKind::ConnectionRefused
+            | IoErrorKind::ConnectionReset
+            | IoErrorKind::Interrupted
+            | IoErrorKind::TimedOut
+            | IoErrorKind::UnexpectedEof
+            | IoErrorKind::WouldBlock
+    )
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match *self {
             #[cfg(feature = "http")]
-            Error::Http(ref inner) => inner.description(),
+            Error::Http(ref inner) => inner.fmt(f),
             #[cfg(feature = "hyper")]
-            Error::Hyper(ref inner) => inner.description(),
+            Error::Hyper(ref inner) => inner.fmt(f),
+            Error::InvalidGuildId(ref id) => {
+                write!(f, "Invalid guild ID: {:?}", id)
+            },
             #[cfg(feature = "reqwest")]
-            Error::InvalidHeaderValue(ref inner) => inner.description(),
-            Error::Io(ref inner) => inner.description(),
-            Error::Json(ref inner) => inner.description(),
-            Error::PlayerAlreadyExists => "Player already exists for the guild",
+            Error::InvalidHeaderValue(ref inner) => inner.fmt(f),
+            Error::InvalidHost(ref host) => write!(
+                f,
+                "Invalid host {:?}: expected host:port or an http(s) URL",
+                host,
+            ),
+            Error::InvalidIdentifier(ref inner) => inner.fmt(f),
+            Error::InvalidNodeVersion(ref version) => {
+                write!(f, "Invalid node version: {:?}", version)
+            },
+            Error::Io(ref inner) => inner.fmt(f),
+            Error::Json(ref inner) => inner.fmt(f),
+            Error::MalformedMessage(ref inner) => inner.fmt(f),
+            Error::MissingSessionId => f.write_str("No session ID was set"),
+            Error::PlayerAlreadyExists => {
+                f.write_str("Player already exists for the guild")
+            },
             #[cfg(feature = "reqwest")]
-            Error::Reqwest(ref inner) => inner.description(),
-            Error::Send(ref inner) => inner,
+            Error::Reqwest(ref inner) => inner.fmt(f),
+            Error::ReservedHeader(ref name) => {
+                write!(f, "Header {:?} is set by the library", name)
+            },
+            Error::Send(ref inner) => f.write_str(inner),
+            Error::Shared(ref inner) => inner.fmt(f),
+            Error::Status(ref inner) => inner.fmt(f),
+            Error::UnknownEvent(ref kind) => {
+                write!(f, "Unknown event type: {:?}", kind)
+            },
+            Error::UnknownOpcode(ref op) => {
+                write!(f, "Unknown opcode: {:?}", op)
+            },
             #[cfg(feature = "http")]
-            Error::Uri(ref inner) => inner.description(),
-            Error::ParseUtf8(ref inner) => inner.description(),
-            Error::Base64Error(ref inner) => inner.description(),
+            Error::Uri(ref inner) => inner.fmt(f),
+            Error::ParseUtf8(ref inner) => inner.fmt(f),
+            Error::Base64Error(ref inner) => inner.fmt(f),
         }
     }
 }
 
+impl StdError for Error {}
+
 #[cfg(feature = "http")]
 impl From<HttpError> for Error {
     fn from(err: HttpError) -> Self {
@@ -143,6 +364,12 @@ impl From<InvalidUri> for Error {
     }
 }
 
+impl From<UnknownOpcodeError> for Error {
+    fn from(err: UnknownOpcodeError) -> Self {
+        Error::UnknownOpcode(err.into_string())
+    }
+}
+
 impl From<FromUtf8Error> for Error {
     fn from(error: FromUtf8Error) -> Self {
         Error::ParseUtf8(error)
@@ -154,3 +381,39 @@ impl From<DecodeError> for Error {
         Error::Base64Error(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::StatusError;
+    use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+    use std::sync::Arc;
+    use super::Error;
+
+    fn status(status: u16) -> Error {
+        Error::Status(StatusError {
+            body: String::new(),
+            status,
+        })
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        // only transport errors are retryable
+        assert!(!Error::InvalidHost("ws://127.0.0.1".to_owned()).is_retryable());
+        assert!(!Error::Send("receiver dropped".to_owned()).is_retryable());
+
+        let io = |kind| Error::Io(IoError::new(kind, "io"));
+        assert!(io(IoErrorKind::ConnectionReset).is_retryable());
+        assert!(io(IoErrorKind::TimedOut).is_retryable());
+        assert!(!io(IoErrorKind::PermissionDenied).is_retryable());
+
+        assert!(status(503).is_retryable());
+        assert!(status(429).is_retryable());
+        assert!(!status(401).is_retryable());
+        assert!(!status(404).is_retryable());
+
+        let shared = |error| Error::Shared(Arc::new(error));
+        assert!(shared(status(500)).is_retryable());
+        assert!(!shared(io(IoErrorKind::PermissionDenied)).is_retryable());
+    }
+}
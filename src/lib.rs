@@ -15,7 +15,7 @@
 //!
 //! ### Installation
 //!
-//! This library requires at least Rust 1.31.0.
+//! This library requires at least Rust 1.45.0.
 //!
 //! If using `lavalink.rs` directly, add the following to your `Cargo.toml`:
 //!
@@ -45,8 +45,8 @@
 //! [license]: https://github.com/serenity-rs/lavalink.rs/blob/master/LICENSE.md
 //! [license]: https://opensource.org/licenses/ISC
 //! [license-badge]: https://img.shields.io/badge/license-ISC-blue.svg?style=flat-square
-//! [rust badge]: https://img.shields.io/badge/rust-1.31.0+-93450a.svg?style=flat-square
-//! [rust link]: https://blog.rust-lang.org/2018/12/06/Rust-1.31-and-rust-2018.html
+//! [rust badge]: https://img.shields.io/badge/rust-1.45.0+-93450a.svg?style=flat-square
+//! [rust link]: https://blog.rust-lang.org/2020/07/16/Rust-1.45.0.html
 #![deny(missing_docs)]
 
 #[macro_use]
@@ -56,7 +56,6 @@ extern crate serde_derive;
 #[macro_use]
 extern crate log;
 
-extern crate percent_encoding;
 extern crate serde;
 extern crate serde_json;
 extern crate base64;
@@ -71,12 +70,22 @@ extern crate hyper;
 #[cfg(feature = "reqwest")]
 extern crate reqwest;
 
+pub mod credentials;
 pub mod model;
 pub mod opcodes;
+pub mod prelude;
+pub mod ramp;
+pub mod recording;
+pub mod remote;
 pub mod rest;
+pub mod sanitize;
+pub mod sharding;
 pub mod decoder;
+pub mod snowflake;
+pub mod synthetic;
 
 mod error;
-mod prelude;
+mod secret;
 
-pub use error::{Error, Result};
+pub use error::{Error, ErrorKind, Result};
+pub use secret::Secret;
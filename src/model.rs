@@ -1,11 +1,15 @@
 //! A collection of messages to send to and receive from the LavaLink node.
 
-use serde::Serializer;
+use crate::{Error, Result};
+use serde::de::{Deserializer, Error as DeError, Unexpected, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{self, Error as JsonError, Map, Number, Value};
 use super::opcodes::Opcode;
 use std::{
     error::Error as StdError,
     fmt::{Display, Formatter, Result as FmtResult},
     result::Result as StdResult,
+    time::Duration,
 };
 
 /// A representation of an error that occurred while creating a [`Band`].
@@ -29,39 +33,381 @@ pub enum BandError {
 
 impl Display for BandError {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        f.write_str(self.description())
-    }
-}
-
-impl StdError for BandError {
-    fn description(&self) -> &str {
         use self::BandError::*;
 
-        match self {
+        f.write_str(match self {
             BandInvalid => "The band value is not within range",
             GainInvalid => "The gain value is not within range",
-        }
+        })
     }
 }
 
+impl StdError for BandError {}
+
 /// An incoming message from the node.
+///
+/// Every message can also be constructed, such as to test code that handles
+/// messages without a node.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::model::{
+///     Event,
+///     EventTrackEnd,
+///     IncomingMessage,
+///     PlayerUpdate,
+///     Ready,
+///     Stats,
+/// };
+///
+/// let messages = vec![
+///     IncomingMessage::Event(Event::TrackEnd(EventTrackEnd::new(
+///         "381880193251409931",
+///         "FINISHED",
+///         "QAAAjQIAJVJpY2sgQXN0bGV5",
+///     ))),
+///     IncomingMessage::PlayerUpdate(PlayerUpdate::new(
+///         "381880193251409931",
+///         1535170125,
+///         60000,
+///     )),
+///     IncomingMessage::Ready(Ready::new(false, "la3kfsdf5eafe848")),
+///     IncomingMessage::Stats(Stats::builder().players(1).build()),
+/// ];
+/// ```
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(untagged)]
 pub enum IncomingMessage {
     /// Indicator that this is an event from the server.
     Event(Event),
     /// Indicator that this is a PlayerUpdate payload.
     PlayerUpdate(PlayerUpdate),
+    /// Indicator that this is a Ready payload.
+    Ready(Ready),
     /// Indicator that this is a Stats payload.
     Stats(Stats),
+    /// A message with an opcode or event type that isn't known, kept as the
+    /// raw JSON value.
+    ///
+    /// This is only produced by [`IncomingMessage::parse`] in
+    /// [`ParseMode::Lenient`].
+    ///
+    /// [`IncomingMessage::parse`]: #method.parse
+    /// [`ParseMode::Lenient`]: enum.ParseMode.html#variant.Lenient
+    #[serde(skip_deserializing)]
+    Unknown(Value),
+}
+
+impl IncomingMessage {
+    /// Parses a message from the node, handling messages with an unknown
+    /// opcode or event type according to the given mode.
+    ///
+    /// Unlike deserializing with serde directly, this checks the opcode
+    /// before choosing a variant, so an unknown message can't be mistaken
+    /// for a known one with a similar shape.
+    ///
+    /// Events from Lavalink v4 nodes include the track as an object, which is
+    /// read as its encoded track and its `userData`, if any. The object's
+    /// other fields are kept in the event's `track_details`, so that the event
+    /// is serialized with the same track object again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::model::{IncomingMessage, ParseMode};
+    ///
+    /// let payload = r#"{"op":"lyrics","guildId":"1"}"#;
+    ///
+    /// let message = IncomingMessage::parse(payload, ParseMode::Lenient)?;
+    /// assert!(message.is_unknown());
+    ///
+    /// assert!(IncomingMessage::parse(payload, ParseMode::Strict).is_err());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownOpcode`] or [`Error::UnknownEvent`] in
+    /// [`ParseMode::Strict`] if the message isn't known, and [`Error::Json`]
+    /// if a known message is malformed.
+    ///
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    /// [`Error::UnknownEvent`]: ../enum.Error.html#variant.UnknownEvent
+    /// [`Error::UnknownOpcode`]: ../enum.Error.html#variant.UnknownOpcode
+    /// [`ParseMode::Strict`]: enum.ParseMode.html#variant.Strict
+    #[inline]
+    pub fn parse(payload: impl AsRef<str>, mode: ParseMode) -> Result<Self> {
+        Self::_parse(payload.as_ref(), mode)
+    }
+
+    fn _parse(payload: &str, mode: ParseMode) -> Result<Self> {
+        Self::parse_value(serde_json::from_str(payload)?, mode)
+    }
+
+    /// Parses a message from the node directly from the bytes of a frame,
+    /// handling messages with an unknown opcode or event type according to
+    /// the given mode.
+    ///
+    /// This is the same as [`parse`], without first converting the payload
+    /// to a string. Errors from malformed payloads also describe the payload.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::model::{IncomingMessage, ParseMode};
+    /// use lavalink::Error;
+    ///
+    /// let payload = br#"{"op":"playerUpdate","guildId":"1","state":{}}"#;
+    ///
+    /// match IncomingMessage::from_slice(payload, ParseMode::Strict) {
+    ///     Err(Error::MalformedMessage(why)) => {
+    ///         assert_eq!(why.op(), Some("playerUpdate"));
+    ///     },
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownOpcode`] or [`Error::UnknownEvent`] in
+    /// [`ParseMode::Strict`] if the message isn't known, and
+    /// [`Error::MalformedMessage`] if the payload isn't valid JSON or a known
+    /// message is malformed.
+    ///
+    /// [`Error::MalformedMessage`]: ../enum.Error.html#variant.MalformedMessage
+    /// [`Error::UnknownEvent`]: ../enum.Error.html#variant.UnknownEvent
+    /// [`Error::UnknownOpcode`]: ../enum.Error.html#variant.UnknownOpcode
+    /// [`ParseMode::Strict`]: enum.ParseMode.html#variant.Strict
+    /// [`parse`]: #method.parse
+    pub fn from_slice(payload: &[u8], mode: ParseMode) -> Result<Self> {
+        let value = serde_json::from_slice::<Value>(payload).map_err(|why| {
+            MalformedMessage::error(payload, None, why)
+        })?;
+        let op = value.get("op").and_then(Value::as_str).map(ToOwned::to_owned);
+
+        Self::parse_value(value, mode).map_err(|why| match why {
+            Error::Json(why) => MalformedMessage::error(payload, op, why),
+            other => other,
+        })
+    }
+
+    pub(crate) fn parse_value(mut value: Value, mode: ParseMode) -> Result<Self> {
+        let details = flatten_event_track(&mut value);
+
+        let op = value.get("op").and_then(Value::as_str).unwrap_or_default();
+
+        let unknown = match op.parse::<Opcode>() {
+            Ok(Opcode::Event) => {
+                let kind = value
+                    .get("type")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+
+                if Event::is_known_type(kind) {
+                    None
+                } else {
+                    Some(Error::UnknownEvent(kind.to_owned()))
+                }
+            },
+            Ok(Opcode::PlayerUpdate) | Ok(Opcode::Ready) | Ok(Opcode::Stats) => {
+                None
+            },
+            _ => Some(Error::UnknownOpcode(op.to_owned())),
+        };
+
+        match (unknown, mode) {
+            (None, _) => {
+                let mut message = serde_json::from_value(value)?;
+
+                if let IncomingMessage::Event(ref mut event) = message {
+                    match event {
+                        Event::TrackEnd(e) => e.track_details = details,
+                        Event::TrackException(e) => e.track_details = details,
+                        Event::TrackStart(e) => e.track_details = details,
+                        Event::TrackStuck(e) => e.track_details = details,
+                        Event::WebSocketClosed(_) => {},
+                    }
+                }
+
+                Ok(message)
+            },
+            (Some(why), ParseMode::Strict) => Err(why),
+            (Some(_), ParseMode::Lenient) => Ok(IncomingMessage::Unknown(value)),
+        }
+    }
+
+    /// Whether the message has an unknown opcode or event type.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, IncomingMessage::Unknown(_))
+    }
+}
+
+/// Replaces the track object of a Lavalink v4 event with its encoded track,
+/// moving the track's user data onto the event.
+///
+/// Returns the other fields of the track object, to be kept in the event's
+/// `track_details`.
+fn flatten_event_track(value: &mut Value) -> Option<Map<String, Value>> {
+    let event = match value.as_object_mut() {
+        Some(event) if event.get("op").and_then(Value::as_str) == Some("event") => {
+            event
+        },
+        _ => return None,
+    };
+    let mut track = match event.remove("track") {
+        Some(Value::Object(track)) => track,
+        Some(other) => {
+            event.insert("track".to_owned(), other);
+
+            return None;
+        },
+        None => return None,
+    };
+
+    if let Some(encoded) = track.remove("encoded") {
+        event.insert("track".to_owned(), encoded);
+    }
+
+    if let Some(user_data) = track.remove("userData") {
+        event.entry("userData").or_insert(user_data);
+    }
+
+    Some(track)
+}
+
+/// Serializes the track of an event, along with its user data, as the track
+/// object that Lavalink v4 nodes send if the event was received from one.
+fn serialize_event_track<M: SerializeMap>(
+    map: &mut M,
+    track: &str,
+    user_data: &Option<Value>,
+    details: &Option<Map<String, Value>>,
+) -> StdResult<(), M::Error> {
+    let details = match details {
+        Some(details) => details,
+        None => {
+            map.serialize_entry("track", track)?;
+
+            if let Some(user_data) = user_data {
+                map.serialize_entry("userData", user_data)?;
+            }
+
+            return Ok(());
+        },
+    };
+
+    let mut object = details.clone();
+    object.insert("encoded".to_owned(), Value::from(track));
+
+    if let Some(user_data) = user_data {
+        object.insert("userData".to_owned(), user_data.clone());
+    }
+
+    map.serialize_entry("track", &object)
+}
+
+/// A payload from the node that couldn't be parsed, returned by
+/// [`IncomingMessage::from_slice`].
+///
+/// [`IncomingMessage::from_slice`]: enum.IncomingMessage.html#method.from_slice
+#[derive(Debug)]
+pub struct MalformedMessage {
+    op: Option<String>,
+    snippet: String,
+    source: JsonError,
+}
+
+impl MalformedMessage {
+    /// The longest part of the payload kept in a [`snippet`], in bytes.
+    ///
+    /// [`snippet`]: #method.snippet
+    pub const SNIPPET_LENGTH: usize = 128;
+
+    fn error(payload: &[u8], op: Option<String>, source: JsonError) -> Error {
+        let mut end = payload.len().min(Self::SNIPPET_LENGTH);
+        // avoid splitting a character at the end of the snippet
+        while end > 0 && end < payload.len() && (payload[end] & 0xC0) == 0x80 {
+            end -= 1;
+        }
+
+        Error::MalformedMessage(Box::new(Self {
+            op,
+            snippet: String::from_utf8_lossy(&payload[..end]).into_owned(),
+            source,
+        }))
+    }
+
+    /// Returns the opcode of the payload, if it could be read.
+    pub fn op(&self) -> Option<&str> {
+        self.op.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Returns the start of the payload, for logging.
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+
+    /// Returns the JSON error from parsing the payload.
+    pub fn json_error(&self) -> &JsonError {
+        &self.source
+    }
+}
+
+impl Display for MalformedMessage {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.op {
+            Some(ref op) => write!(f, "Malformed {:?} message", op)?,
+            None => f.write_str("Malformed message")?,
+        }
+
+        write!(f, ": {} in {:?}", self.source, self.snippet)
+    }
+}
+
+impl StdError for MalformedMessage {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// How [`IncomingMessage::parse`] handles messages with an unknown opcode or
+/// event type.
+///
+/// [`IncomingMessage::parse`]: enum.IncomingMessage.html#method.parse
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ParseMode {
+    /// Keep unknown messages as [`IncomingMessage::Unknown`].
+    ///
+    /// This is the default.
+    ///
+    /// [`IncomingMessage::Unknown`]: enum.IncomingMessage.html#variant.Unknown
+    Lenient,
+    /// Return an error for unknown messages.
+    ///
+    /// This is useful during development to notice when a node sends
+    /// messages that the library doesn't support yet.
+    Strict,
+}
+
+impl Default for ParseMode {
+    fn default() -> Self {
+        ParseMode::Lenient
+    }
 }
 
 /// An outgoing message to the node.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(untagged)]
 pub enum OutgoingMessage {
     /// Indicator that this is a Destroy payload.
     Destroy(Destroy),
+    /// Indicator that this is an Equalizer payload.
+    Equalizer(Equalizer),
     /// Indicator that this is a Pause payload.
     Pause(Pause),
     /// Indicator that this is a Play payload.
@@ -76,8 +422,77 @@ pub enum OutgoingMessage {
     Volume(Volume),
 }
 
+/// A message serialized to be sent to a node as a WebSocket text frame.
+///
+/// This doesn't depend on any WebSocket library, so that custom transports
+/// can use the same serialization as this library. Create one with
+/// [`IntoWebSocketMessage`].
+///
+/// [`IntoWebSocketMessage`]: trait.IntoWebSocketMessage.html
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct WireMessage(String);
+
+impl WireMessage {
+    /// Returns the text of the message.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the text of the message, consuming the message.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl AsRef<str> for WireMessage {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<WireMessage> for String {
+    fn from(message: WireMessage) -> Self {
+        message.0
+    }
+}
+
+/// Trait to implement for converting an outgoing payload into a message to
+/// send to a node.
+///
+/// This is implemented for [`OutgoingMessage`] and each of the outgoing
+/// models.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> lavalink::Result<()> {
+/// use lavalink::model::{IntoWebSocketMessage, Pause};
+///
+/// let message = Pause::new("381880193251409931", true).into_ws_message()?;
+///
+/// assert_eq!(
+///     message.as_str(),
+///     r#"{"guildId":"381880193251409931","op":"pause","pause":true}"#,
+/// );
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`OutgoingMessage`]: enum.OutgoingMessage.html
+pub trait IntoWebSocketMessage {
+    /// Serializes the payload into a message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Json`] if the payload could not be serialized.
+    ///
+    /// [`Error::Json`]: ../enum.Error.html#variant.Json
+    fn into_ws_message(self) -> Result<WireMessage>;
+}
+
 /// A band for an equalizer.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct Band {
     /// There are 15 bands (0-14) that can be changed.
@@ -91,8 +506,6 @@ pub struct Band {
     ///
     /// Modifying the gain could also change the volume of the output.
     pub gain: f64,
-    #[serde(default, skip_deserializing, skip_serializing)]
-    nonexhaustive: (),
 }
 
 impl Band {
@@ -122,18 +535,17 @@ impl Band {
     pub fn new(
         band: u8,
         gain: f64,
-    ) -> Result<Self, BandError> {
+    ) -> StdResult<Self, BandError> {
         if band > 14 {
             return Err(BandError::BandInvalid);
         }
-        if gain < -0.25 || gain > 1.0 {
+        if !(-0.25..=1.0).contains(&gain) {
             return Err(BandError::GainInvalid);
         }
 
         Ok(Self {
             band,
             gain,
-            nonexhaustive: (),
         })
     }
 }
@@ -227,28 +639,195 @@ impl Equalizer {
     }
 }
 
+/// A set of audio filters, as used by nodes supporting filters in place of
+/// the [`Equalizer`] message.
+///
+/// Filters other than the equalizer and volume are kept as raw JSON values
+/// in [`other`], so that no filters are lost when converting.
+///
+/// # Examples
+///
+/// Convert equalizer settings into filters for a Lavalink v4 node:
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use lavalink::model::{Band, Equalizer, Filters};
+///
+/// let equalizer = Equalizer::new("381880193251409931", vec![
+///     Band::new(0, 0.25)?,
+/// ]);
+/// let filters = Filters::from(equalizer);
+///
+/// assert_eq!(filters.equalizer_bands(), &[Band::new(0, 0.25)?][..]);
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`Equalizer`]: struct.Equalizer.html
+/// [`other`]: #structfield.other
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct Filters {
+    /// The bands of the equalizer, if it is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equalizer: Option<Vec<Band>>,
+    /// The other filters, such as `timescale`, by name.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+    /// The volume multiplier, where `1.0` is 100%, if it is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f64>,
+}
+
+impl Filters {
+    /// Creates a new set of filters with none set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the bands of the equalizer, which are empty if it isn't set.
+    pub fn equalizer_bands(&self) -> &[Band] {
+        self.equalizer.as_ref().map(AsRef::as_ref).unwrap_or(&[])
+    }
+
+    /// Creates [`Equalizer`] settings for a guild from the filters'
+    /// equalizer bands.
+    ///
+    /// [`Equalizer`]: struct.Equalizer.html
+    pub fn to_equalizer(&self, guild_id: impl Into<String>) -> Equalizer {
+        Equalizer::new(guild_id, self.equalizer_bands().to_vec())
+    }
+
+    /// The rate of the `timescale` filter set by [`set_nightcore`].
+    ///
+    /// [`set_nightcore`]: #method.set_nightcore
+    pub const NIGHTCORE_RATE: f64 = 1.25;
+
+    /// Sets the playback speed of the `timescale` filter, without changing
+    /// the pitch, where `1.0` is normal speed.
+    ///
+    /// The other values of the `timescale` filter are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::model::Filters;
+    ///
+    /// let mut filters = Filters::new();
+    /// filters.set_speed(1.25);
+    /// filters.set_pitch(-12.0);
+    ///
+    /// assert_eq!(filters.other["timescale"]["speed"], 1.25);
+    /// assert_eq!(filters.other["timescale"]["pitch"], 0.5);
+    /// ```
+    pub fn set_speed(&mut self, speed: f64) {
+        self.set_timescale("speed", speed);
+    }
+
+    /// Sets the pitch of the `timescale` filter in semitones, without
+    /// changing the speed, where `0.0` is the original pitch.
+    ///
+    /// The node takes the pitch as a multiplier, so it is sent as
+    /// `2 ^ (semitones / 12)`.
+    pub fn set_pitch(&mut self, semitones: f64) {
+        self.set_timescale("pitch", (semitones / 12.0).exp2());
+    }
+
+    /// Enables or disables the nightcore effect, which plays the track
+    /// faster and at a higher pitch.
+    ///
+    /// This sets the rate of the `timescale` filter to [`NIGHTCORE_RATE`],
+    /// or back to `1.0`, so it combines with [`set_speed`] and
+    /// [`set_pitch`].
+    ///
+    /// [`NIGHTCORE_RATE`]: #associatedconstant.NIGHTCORE_RATE
+    /// [`set_pitch`]: #method.set_pitch
+    /// [`set_speed`]: #method.set_speed
+    pub fn set_nightcore(&mut self, enabled: bool) {
+        let rate = if enabled { Self::NIGHTCORE_RATE } else { 1.0 };
+
+        self.set_timescale("rate", rate);
+    }
+
+    fn set_timescale(&mut self, key: &str, value: f64) {
+        let value = match Number::from_f64(value) {
+            Some(value) => Value::Number(value),
+            None => return,
+        };
+
+        let timescale = self.other.entry("timescale")
+            .or_insert_with(|| Value::Object(Map::new()));
+
+        if !timescale.is_object() {
+            *timescale = Value::Object(Map::new());
+        }
+
+        if let Value::Object(timescale) = timescale {
+            timescale.insert(key.to_owned(), value);
+        }
+    }
+}
+
+impl From<Equalizer> for Filters {
+    fn from(equalizer: Equalizer) -> Self {
+        Self {
+            equalizer: Some(equalizer.bands),
+            ..Self::default()
+        }
+    }
+}
+
 /// An event from the server.
 ///
+/// The variant is chosen by the event's `type` field, which is included again
+/// when serializing.
+///
 /// **Note**: This is only sent from a node.
 #[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(untagged)]
+#[non_exhaustive]
+#[serde(tag = "type")]
 pub enum Event {
     /// An indicator that a track ended.
+    #[serde(rename = "TrackEndEvent")]
     TrackEnd(EventTrackEnd),
     /// An indicator that an exception occurred while playing a track.
+    #[serde(rename = "TrackExceptionEvent")]
     TrackException(EventTrackException),
+    /// An indicator that a track started playing.
+    #[serde(rename = "TrackStartEvent")]
+    TrackStart(EventTrackStart),
     /// An indicator that a track became stuck.
+    #[serde(rename = "TrackStuckEvent")]
     TrackStuck(EventTrackStuck),
     /// An indicator that a WebSocket connection to Discord closed.
+    #[serde(rename = "WebSocketClosedEvent")]
     WebSocketClosed(EventWebSocketClosed),
 }
 
 impl Event {
+    /// The event types sent by the node, as in an event's `type` field.
+    pub const TYPES: &'static [&'static str] = &[
+        "TrackEndEvent",
+        "TrackExceptionEvent",
+        "TrackStartEvent",
+        "TrackStuckEvent",
+        "WebSocketClosedEvent",
+    ];
+
+    /// Whether the event type is one of the known [`TYPES`].
+    ///
+    /// [`TYPES`]: #associatedconstant.TYPES
+    pub fn is_known_type(kind: &str) -> bool {
+        Self::TYPES.contains(&kind)
+    }
+
     /// Returns the guild ID of the event.
     pub fn guild_id(&self) -> &str {
         match self {
             Event::TrackEnd(e) => &e.guild_id,
             Event::TrackException(e) => &e.guild_id,
+            Event::TrackStart(e) => &e.guild_id,
             Event::TrackStuck(e) => &e.guild_id,
             Event::WebSocketClosed(e) => &e.guild_id,
         }
@@ -258,20 +837,63 @@ impl Event {
 /// A track was ended.
 ///
 /// **Note**: This is only sent from a node.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct EventTrackEnd {
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
     pub guild_id: String,
     /// The reason for the track ending.
     pub reason: String,
     /// The track that ended.
     pub track: String,
+    /// The data attached to the track when it was played, if any.
+    ///
+    /// This is only sent by Lavalink v4 nodes. See [`IncomingMessage::parse`]
+    /// for how the track is read from such nodes.
+    ///
+    /// [`IncomingMessage::parse`]: enum.IncomingMessage.html#method.parse
+    #[serde(default)]
+    pub user_data: Option<Value>,
+    /// The other fields of the track object sent by Lavalink v4 nodes, such
+    /// as `info` and `pluginInfo`.
+    ///
+    /// This is `None` for events from nodes that send only the encoded
+    /// track.
+    #[serde(skip_deserializing)]
+    pub track_details: Option<Map<String, Value>>,
     op: Opcode,
 }
 
+impl Serialize for EventTrackEnd {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.serialize_entry("guildId", &self.guild_id)?;
+        map.serialize_entry("reason", &self.reason)?;
+        serialize_event_track(
+            &mut map,
+            &self.track,
+            &self.user_data,
+            &self.track_details,
+        )?;
+        map.serialize_entry("op", &self.op)?;
+
+        map.end()
+    }
+}
+
 impl EventTrackEnd {
-    /// Creates a new EventTrackException instance.
+    /// Creates a new `EventTrackEnd` event.
     #[inline]
     pub fn new(
         guild_id: impl Into<String>,
@@ -283,10 +905,13 @@ impl EventTrackEnd {
 
     fn _new(guild_id: String, reason: String, track: String) -> Self {
         Self {
+            extra: Map::new(),
             op: Opcode::Event,
             guild_id,
             reason,
             track,
+            user_data: None,
+            track_details: None,
         }
     }
 }
@@ -294,20 +919,63 @@ impl EventTrackEnd {
 /// An exception occurred while playing a track.
 ///
 /// **Note**: This is only sent from a node.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct EventTrackException {
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
     pub guild_id: String,
     /// The reason for the exception.
     pub error: String,
     /// The track that ended.
     pub track: String,
+    /// The data attached to the track when it was played, if any.
+    ///
+    /// This is only sent by Lavalink v4 nodes. See [`IncomingMessage::parse`]
+    /// for how the track is read from such nodes.
+    ///
+    /// [`IncomingMessage::parse`]: enum.IncomingMessage.html#method.parse
+    #[serde(default)]
+    pub user_data: Option<Value>,
+    /// The other fields of the track object sent by Lavalink v4 nodes, such
+    /// as `info` and `pluginInfo`.
+    ///
+    /// This is `None` for events from nodes that send only the encoded
+    /// track.
+    #[serde(skip_deserializing)]
+    pub track_details: Option<Map<String, Value>>,
     op: Opcode,
 }
 
+impl Serialize for EventTrackException {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.serialize_entry("guildId", &self.guild_id)?;
+        map.serialize_entry("error", &self.error)?;
+        serialize_event_track(
+            &mut map,
+            &self.track,
+            &self.user_data,
+            &self.track_details,
+        )?;
+        map.serialize_entry("op", &self.op)?;
+
+        map.end()
+    }
+}
+
 impl EventTrackException {
-    /// Creates a new EventTrackException instance.
+    /// Creates a new `EventTrackException` event.
     #[inline]
     pub fn new(
         guild_id: impl Into<String>,
@@ -319,10 +987,87 @@ impl EventTrackException {
 
     fn _new(guild_id: String, error: String, track: String) -> Self {
         Self {
+            extra: Map::new(),
             op: Opcode::Event,
             error,
             guild_id,
             track,
+            user_data: None,
+            track_details: None,
+        }
+    }
+}
+
+/// A track started playing.
+///
+/// **Note**: This is only sent from a node.
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct EventTrackStart {
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
+    pub guild_id: String,
+    /// The track that started.
+    pub track: String,
+    /// The data attached to the track when it was played, if any.
+    ///
+    /// This is only sent by Lavalink v4 nodes. See [`IncomingMessage::parse`]
+    /// for how the track is read from such nodes.
+    ///
+    /// [`IncomingMessage::parse`]: enum.IncomingMessage.html#method.parse
+    #[serde(default)]
+    pub user_data: Option<Value>,
+    /// The other fields of the track object sent by Lavalink v4 nodes, such
+    /// as `info` and `pluginInfo`.
+    ///
+    /// This is `None` for events from nodes that send only the encoded
+    /// track.
+    #[serde(skip_deserializing)]
+    pub track_details: Option<Map<String, Value>>,
+    op: Opcode,
+}
+
+impl Serialize for EventTrackStart {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.serialize_entry("guildId", &self.guild_id)?;
+        serialize_event_track(
+            &mut map,
+            &self.track,
+            &self.user_data,
+            &self.track_details,
+        )?;
+        map.serialize_entry("op", &self.op)?;
+
+        map.end()
+    }
+}
+
+impl EventTrackStart {
+    /// Creates a new `EventTrackStart` event.
+    #[inline]
+    pub fn new(guild_id: impl Into<String>, track: impl Into<String>) -> Self {
+        Self::_new(guild_id.into(), track.into())
+    }
+
+    fn _new(guild_id: String, track: String) -> Self {
+        Self {
+            extra: Map::new(),
+            op: Opcode::Event,
+            guild_id,
+            track,
+            user_data: None,
+            track_details: None,
         }
     }
 }
@@ -330,20 +1075,68 @@ impl EventTrackException {
 /// A track became stuck.
 ///
 /// **Note**: This is only sent from a node.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct EventTrackStuck {
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
     pub guild_id: String,
-    /// The reason for the track ending.
+    /// How long the track had been stuck for in milliseconds, without
+    /// receiving any audio, when the node reported it.
+    ///
+    /// Use [`threshold`] to get this as a `Duration`.
+    ///
+    /// [`threshold`]: #method.threshold
     pub threshold_ms: i64,
-    /// The track that ended.
+    /// The track that became stuck.
     pub track: String,
+    /// The data attached to the track when it was played, if any.
+    ///
+    /// This is only sent by Lavalink v4 nodes. See [`IncomingMessage::parse`]
+    /// for how the track is read from such nodes.
+    ///
+    /// [`IncomingMessage::parse`]: enum.IncomingMessage.html#method.parse
+    #[serde(default)]
+    pub user_data: Option<Value>,
+    /// The other fields of the track object sent by Lavalink v4 nodes, such
+    /// as `info` and `pluginInfo`.
+    ///
+    /// This is `None` for events from nodes that send only the encoded
+    /// track.
+    #[serde(skip_deserializing)]
+    pub track_details: Option<Map<String, Value>>,
     op: Opcode,
 }
 
+impl Serialize for EventTrackStuck {
+    fn serialize<S: Serializer>(&self, serializer: S) -> StdResult<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.serialize_entry("guildId", &self.guild_id)?;
+        map.serialize_entry("thresholdMs", &self.threshold_ms)?;
+        serialize_event_track(
+            &mut map,
+            &self.track,
+            &self.user_data,
+            &self.track_details,
+        )?;
+        map.serialize_entry("op", &self.op)?;
+
+        map.end()
+    }
+}
+
 impl EventTrackStuck {
-    /// Creates a new EventTrackStuck instance.
+    /// Creates a new `EventTrackStuck` event.
     #[inline]
     pub fn new(
         guild_id: impl Into<String>,
@@ -355,25 +1148,72 @@ impl EventTrackStuck {
 
     fn _new(guild_id: String, threshold_ms: i64, track: String) -> Self {
         Self {
+            extra: Map::new(),
             op: Opcode::Event,
             guild_id,
             threshold_ms,
             track,
+            user_data: None,
+            track_details: None,
         }
     }
+
+    /// Creates a new `EventTrackStuck` event from a `Duration`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::model::EventTrackStuck;
+    /// use std::time::Duration;
+    ///
+    /// let event = EventTrackStuck::new_duration(
+    ///     "381880193251409931",
+    ///     Duration::from_secs(10),
+    ///     "QAAAjQIAJVJpY2sgQXN0bGV5",
+    /// );
+    ///
+    /// assert_eq!(event.threshold_ms, 10_000);
+    /// assert_eq!(event.threshold(), Duration::from_secs(10));
+    /// ```
+    #[inline]
+    pub fn new_duration(
+        guild_id: impl Into<String>,
+        threshold: Duration,
+        track: impl Into<String>,
+    ) -> Self {
+        Self::_new(
+            guild_id.into(),
+            millis_from_duration(threshold),
+            track.into(),
+        )
+    }
+
+    /// Returns how long the track had been stuck for when the node reported
+    /// it.
+    ///
+    /// A negative threshold from the node is treated as zero.
+    pub fn threshold(&self) -> Duration {
+        duration_from_millis(self.threshold_ms)
+    }
 }
 
 /// A WebSocket connection to Discord closed.
 ///
 /// **Note**: This is only sent from a node.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct EventWebSocketClosed {
     /// Whether the remote host closed the connection.
     pub by_remote: bool,
     /// The close code from Discord.
     pub code: u16,
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
     pub guild_id: String,
     /// The reason for the closing.
     pub reason: String,
@@ -381,7 +1221,7 @@ pub struct EventWebSocketClosed {
 }
 
 impl EventWebSocketClosed {
-    /// Creates a new EventWebSocketClosed instance.
+    /// Creates a new `EventWebSocketClosed` event.
     #[inline]
     pub fn new(
         guild_id: impl Into<String>,
@@ -399,6 +1239,7 @@ impl EventWebSocketClosed {
         reason: String,
     ) -> Self {
         Self {
+            extra: Map::new(),
             op: Opcode::Event,
             by_remote,
             code,
@@ -469,6 +1310,25 @@ pub struct Play {
     pub start_time: Option<u64>,
     /// The base64 encoded track information.
     pub track: String,
+    /// The volume to start playing the track at, on a scale of 0 to 1000.
+    ///
+    /// If set to `None`, the player's current volume is kept. Setting this
+    /// avoids sending a separate [`Volume`] message after the track started.
+    ///
+    /// # Examples
+    ///
+    /// Play a song at half volume:
+    ///
+    /// ```rust,no_run
+    /// use lavalink::model::Play;
+    ///
+    /// let mut msg = Play::new("381880193251409931", "info here", None, None);
+    /// msg.volume = Some(50);
+    /// ```
+    ///
+    /// [`Volume`]: struct.Volume.html
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<i32>,
 }
 
 impl Play {
@@ -509,6 +1369,7 @@ impl Play {
             guild_id,
             start_time,
             track,
+            volume: None,
         }
     }
 
@@ -546,6 +1407,7 @@ impl Play {
             no_replace,
             start_time,
             track,
+            volume: None,
         }
     }
 }
@@ -556,7 +1418,12 @@ impl Play {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PlayerUpdate {
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The ID of the guild.
+    #[serde(deserialize_with = "deserialize_guild_id")]
     pub guild_id: String,
     op: Opcode,
     /// The new state information.
@@ -587,6 +1454,7 @@ impl PlayerUpdate {
 
     fn _new(guild_id: String, time: u64, position: i64) -> Self {
         Self {
+            extra: Map::new(),
             op: Opcode::PlayerUpdate,
             state: PlayerUpdateState::new(time, position),
             guild_id,
@@ -598,30 +1466,100 @@ impl PlayerUpdate {
 ///
 /// **Note**: This is only received from a node.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 pub struct PlayerUpdateState {
-    /// The current position of the player.
+    /// Whether the node is connected to the voice gateway.
+    ///
+    /// This is only sent by Lavalink v4 nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connected: Option<bool>,
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    /// The ping of the node to the Discord voice server in milliseconds, or
+    /// `-1` if not connected.
+    ///
+    /// This is only sent by Lavalink v4 nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping: Option<i64>,
+    /// The current position of the player in milliseconds.
     pub position: Option<i64>,
-    /// The Unix timestamp of the update.
+    /// The Unix timestamp of the update in milliseconds.
     pub time: u64,
 }
 
-impl PlayerUpdateState {
-    /// Creates a new set of `PlayerUpdateState` data.
+impl PlayerUpdateState {
+    /// Creates a new set of `PlayerUpdateState` data.
+    ///
+    /// # Examples
+    ///
+    /// Create a new player update state with position 60000 at timestamp
+    /// 1535170125:
+    ///
+    /// ```rust,no_run
+    /// use lavalink::model::PlayerUpdateState;
+    ///
+    /// let _msg = PlayerUpdateState::new(60000, 1535170125);
+    /// ```
+    pub fn new(time: u64, position: i64) -> Self {
+        Self {
+            extra: Map::new(),
+            connected: None,
+            ping: None,
+            position: Some(position),
+            time,
+        }
+    }
+
+    /// Returns the current position of the player, if any.
+    pub fn position(&self) -> Option<Duration> {
+        self.position.map(duration_from_millis)
+    }
+}
+
+/// The first message sent by a Lavalink v4 node after connecting, containing
+/// the session ID used by the REST API.
+///
+/// Pass the session ID to the REST client's `set_session_id` so that its v4
+/// session and player methods can be used.
+///
+/// **Note**: This is only received from a node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ready {
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+    op: Opcode,
+    /// Whether a previous session was resumed.
+    pub resumed: bool,
+    /// The ID of the session.
+    pub session_id: String,
+}
+
+impl Ready {
+    /// Creates a new `Ready` message.
     ///
     /// # Examples
     ///
-    /// Create a new player update state with position 60000 at timestamp
-    /// 1535170125:
-    ///
     /// ```rust,no_run
-    /// use lavalink::model::PlayerUpdateState;
+    /// use lavalink::model::Ready;
     ///
-    /// let _msg = PlayerUpdateState::new(60000, 1535170125);
+    /// let _msg = Ready::new(false, "la3kfsdf5eafe848");
     /// ```
-    pub fn new(time: u64, position: i64) -> Self {
+    #[inline]
+    pub fn new(resumed: bool, session_id: impl Into<String>) -> Self {
+        Self::_new(resumed, session_id.into())
+    }
+
+    fn _new(resumed: bool, session_id: String) -> Self {
         Self {
-            position: Some(position),
-            time,
+            extra: Map::new(),
+            op: Opcode::Ready,
+            resumed,
+            session_id,
         }
     }
 }
@@ -633,7 +1571,7 @@ pub struct Seek {
     /// The ID of the guild whose player's position is being seeked.
     pub guild_id: String,
     op: Opcode,
-    /// The time position to seek to.
+    /// The time position to seek to in milliseconds.
     pub position: i64,
 }
 
@@ -661,6 +1599,28 @@ impl Seek {
             position,
         }
     }
+
+    /// Creates a new `Seek` message from a `Duration`.
+    ///
+    /// # Examples
+    ///
+    /// Create a new message to seek a guild's position to 30 seconds:
+    ///
+    /// ```rust,no_run
+    /// use lavalink::model::Seek;
+    /// use std::time::Duration;
+    ///
+    /// let _msg = Seek::new_duration("381880193251409931", Duration::from_secs(30));
+    /// ```
+    #[inline]
+    pub fn new_duration(guild_id: impl Into<String>, position: Duration) -> Self {
+        Self::_new(guild_id.into(), millis_from_duration(position))
+    }
+
+    /// Returns the time position to seek to.
+    pub fn position(&self) -> Duration {
+        duration_from_millis(self.position)
+    }
 }
 
 /// A payload containing statistics about a node.
@@ -671,6 +1631,10 @@ impl Seek {
 pub struct Stats {
     /// The CPU usage of the node.
     pub cpu: StatsCpu,
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The frame information of the node.
     #[serde(rename = "frameStats")]
     pub frames: Option<StatsFrames>,
@@ -685,45 +1649,319 @@ pub struct Stats {
     op: Opcode,
 }
 
+impl Stats {
+    /// Creates a builder for a `Stats` payload, such as to test code that
+    /// handles statistics from a node.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::model::{Stats, StatsCpu};
+    ///
+    /// let stats = Stats::builder()
+    ///     .cpu(StatsCpu::new(4, 0.5, 0.25))
+    ///     .players(10)
+    ///     .playing_players(8)
+    ///     .build();
+    ///
+    /// assert_eq!(stats.playing_players, 8);
+    /// ```
+    pub fn builder() -> StatsBuilder {
+        StatsBuilder::new()
+    }
+
+    /// Returns the changes between the previous stats from the node and
+    /// these.
+    ///
+    /// # Examples
+    ///
+    /// Log how a node changed each minute:
+    ///
+    /// ```rust
+    /// use lavalink::model::Stats;
+    ///
+    /// let previous = Stats::builder().players(10).uptime(60_000).build();
+    /// let current = Stats::builder().players(12).uptime(120_000).build();
+    ///
+    /// let delta = current.delta(&previous);
+    /// assert_eq!(delta.players, 2);
+    /// assert_eq!(delta.uptime, 60_000);
+    /// assert!(!delta.restarted);
+    /// ```
+    pub fn delta(&self, previous: &Stats) -> StatsDelta {
+        let frames = match (&self.frames, &previous.frames) {
+            (None, None) => None,
+            (current, previous) => {
+                // nodes don't send frame stats while no players are playing
+                let zero = StatsFrames::new(0, 0, 0);
+                let current = current.as_ref().unwrap_or(&zero);
+                let previous = previous.as_ref().unwrap_or(&zero);
+
+                Some(StatsFramesDelta {
+                    deficit: current.deficit - previous.deficit,
+                    loss_ratio: current.loss_ratio() - previous.loss_ratio(),
+                    nulled: current.nulled - previous.nulled,
+                    sent: current.sent - previous.sent,
+                })
+            },
+        };
+
+        StatsDelta {
+            frames,
+            players: self.players - previous.players,
+            playing_players: self.playing_players - previous.playing_players,
+            restarted: self.uptime < previous.uptime,
+            uptime: self.uptime - previous.uptime,
+        }
+    }
+}
+
+impl Default for Stats {
+    /// Creates a `Stats` payload with every value set to zero.
+    fn default() -> Self {
+        StatsBuilder::new().build()
+    }
+}
+
+/// The changes between two consecutive [`Stats`] payloads from a node,
+/// created with [`Stats::delta`].
+///
+/// Each value is the current value minus the previous one.
+///
+/// [`Stats`]: struct.Stats.html
+/// [`Stats::delta`]: struct.Stats.html#method.delta
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StatsDelta {
+    /// The changes of the frame statistics, if either payload had them.
+    ///
+    /// A payload without frame statistics is treated as having no frames.
+    pub frames: Option<StatsFramesDelta>,
+    /// The change of the number of players.
+    pub players: i32,
+    /// The change of the number of active players.
+    pub playing_players: i32,
+    /// Whether the node restarted between the payloads, as its uptime went
+    /// down.
+    pub restarted: bool,
+    /// The time between the payloads in milliseconds, according to the
+    /// node's uptime.
+    ///
+    /// This is negative if the node restarted.
+    pub uptime: i64,
+}
+
+/// The changes between two consecutive [`StatsFrames`], as part of a
+/// [`StatsDelta`].
+///
+/// [`StatsDelta`]: struct.StatsDelta.html
+/// [`StatsFrames`]: struct.StatsFrames.html
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct StatsFramesDelta {
+    /// The change of the average number of frames that were expected but
+    /// not sent.
+    pub deficit: i64,
+    /// The change of the ratio of expected frames that were not sent with
+    /// audio.
+    pub loss_ratio: f64,
+    /// The change of the average number of frames that were nulled.
+    pub nulled: i64,
+    /// The change of the average number of frames sent to Discord.
+    pub sent: i64,
+}
+
+/// A builder for a [`Stats`] payload.
+///
+/// Values that aren't set default to zero, and the frame statistics default
+/// to `None`.
+///
+/// [`Stats`]: struct.Stats.html
+#[derive(Clone, Debug, Default)]
+pub struct StatsBuilder {
+    cpu: StatsCpu,
+    frames: Option<StatsFrames>,
+    memory: StatsMemory,
+    players: i32,
+    playing_players: i32,
+    uptime: i64,
+}
+
+impl StatsBuilder {
+    /// Creates a new builder with every value set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the CPU usage of the node.
+    pub fn cpu(mut self, cpu: StatsCpu) -> Self {
+        self.cpu = cpu;
+
+        self
+    }
+
+    /// Sets the frame statistics of the node.
+    pub fn frames(mut self, frames: impl Into<Option<StatsFrames>>) -> Self {
+        self.frames = frames.into();
+
+        self
+    }
+
+    /// Sets the memory usage of the node.
+    pub fn memory(mut self, memory: StatsMemory) -> Self {
+        self.memory = memory;
+
+        self
+    }
+
+    /// Sets the number of players, both active and inactive.
+    pub fn players(mut self, players: i32) -> Self {
+        self.players = players;
+
+        self
+    }
+
+    /// Sets the number of active players.
+    pub fn playing_players(mut self, playing_players: i32) -> Self {
+        self.playing_players = playing_players;
+
+        self
+    }
+
+    /// Sets the uptime of the node in milliseconds.
+    pub fn uptime(mut self, uptime: i64) -> Self {
+        self.uptime = uptime;
+
+        self
+    }
+
+    /// Builds the `Stats` payload.
+    pub fn build(self) -> Stats {
+        Stats {
+            extra: Map::new(),
+            cpu: self.cpu,
+            frames: self.frames,
+            memory: self.memory,
+            players: self.players,
+            playing_players: self.playing_players,
+            uptime: self.uptime,
+            op: Opcode::Stats,
+        }
+    }
+}
+
 /// The CPU usage of a node.
 ///
 /// **Note**: This is only received from a node.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct StatsCpu {
     /// The number of CPU cores available.
     pub cores: i64,
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The system load.
     pub system_load: f64,
     /// The lavalink node load.
     pub lavalink_load: f64,
 }
 
+impl StatsCpu {
+    /// Creates new CPU statistics.
+    pub fn new(cores: i64, system_load: f64, lavalink_load: f64) -> Self {
+        Self {
+            extra: Map::new(),
+            cores,
+            system_load,
+            lavalink_load,
+        }
+    }
+}
+
 /// The statistics about a node's frames.
 ///
+/// The counts are averages per player over the last minute. A player is
+/// expected to send 3000 frames per minute, so `sent + nulled + deficit` is
+/// normally 3000.
+///
 /// **Note**: This is only received from a node.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct StatsFrames {
-    /// The average number of frames sent per minute.
-    #[serde(rename = "sent")]
-    pub average_sent_per_minute: f64,
-    /// The average number of nulled frames per minute.
-    #[serde(rename = "nulled")]
-    pub average_nulled_per_minute: f64,
-    /// The average frame deficit per minute.
-    #[serde(rename = "deficit")]
-    pub average_deficit_per_minute: f64,
+    /// The average number of frames sent to Discord.
+    pub sent: i64,
+    /// The average number of frames that were nulled, sent as silence.
+    pub nulled: i64,
+    /// The average number of frames that were expected but not sent.
+    ///
+    /// This is negative when more frames than expected were sent.
+    pub deficit: i64,
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl StatsFrames {
+    /// Creates new frame statistics.
+    pub fn new(sent: i64, nulled: i64, deficit: i64) -> Self {
+        Self {
+            extra: Map::new(),
+            sent,
+            nulled,
+            deficit,
+        }
+    }
+
+    /// Returns the average number of frames that were expected to be sent.
+    pub fn expected_frame_count(&self) -> i64 {
+        self.sent + self.nulled + self.deficit
+    }
+
+    /// Returns the ratio of expected frames that were not sent with audio,
+    /// either because they were nulled or missing.
+    ///
+    /// This is `0.0` when no frames were expected, and can be negative when
+    /// more frames than expected were sent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::model::StatsFrames;
+    ///
+    /// let frames = StatsFrames::new(2850, 100, 50);
+    ///
+    /// assert_eq!(frames.expected_frame_count(), 3000);
+    /// assert_eq!(frames.loss_ratio(), 0.05);
+    /// ```
+    pub fn loss_ratio(&self) -> f64 {
+        let expected = self.expected_frame_count();
+
+        if expected == 0 {
+            return 0.0;
+        }
+
+        (self.nulled + self.deficit) as f64 / expected as f64
+    }
 }
 
 /// The memory usage of a node.
 ///
 /// **Note**: This is only received from a node.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct StatsMemory {
     /// The allocated amount of memory.
     pub allocated: i64,
+    /// Fields that the library doesn't know about, kept so that no data is
+    /// lost when the message is serialized again.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
     /// The free amount of memory.
     pub free: i64,
     /// The reservable amount of memory.
@@ -732,6 +1970,19 @@ pub struct StatsMemory {
     pub used: i64,
 }
 
+impl StatsMemory {
+    /// Creates new memory statistics.
+    pub fn new(allocated: i64, free: i64, reservable: i64, used: i64) -> Self {
+        Self {
+            extra: Map::new(),
+            allocated,
+            free,
+            reservable,
+            used,
+        }
+    }
+}
+
 /// A message sent to a node to stop a guild's audio player.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -816,6 +2067,7 @@ impl VoiceUpdate {
 ///
 /// [`VoiceUpdate`]: struct.VoiceUpdate.html
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "snake_case")]
 pub struct VoiceUpdateEvent {
     /// The endpoint of the voice state.
@@ -884,14 +2136,63 @@ impl Volume {
     }
 }
 
+/// Converts milliseconds received from a node to a `Duration`, treating
+/// negative values as zero.
+pub(crate) fn duration_from_millis(millis: i64) -> Duration {
+    Duration::from_millis(millis.max(0) as u64)
+}
+
+/// Converts a `Duration` to milliseconds to send to a node, saturating at
+/// `i64::MAX`.
+pub(crate) fn millis_from_duration(duration: Duration) -> i64 {
+    let millis = duration.as_secs()
+        .saturating_mul(1000)
+        .saturating_add(u64::from(duration.subsec_millis()));
+
+    millis.min(i64::MAX as u64) as i64
+}
+
+/// Deserializes a guild ID from either a string or a number, as some proxies
+/// send guild IDs as JSON numbers.
+pub(crate) fn deserialize_guild_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> StdResult<String, D::Error> {
+    struct GuildIdVisitor;
+
+    impl<'de> Visitor<'de> for GuildIdVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut Formatter) -> FmtResult {
+            f.write_str("a guild ID as a string or integer")
+        }
+
+        fn visit_str<E: DeError>(self, value: &str) -> StdResult<String, E> {
+            Ok(value.to_owned())
+        }
+
+        fn visit_string<E: DeError>(self, value: String) -> StdResult<String, E> {
+            Ok(value)
+        }
+
+        fn visit_u64<E: DeError>(self, value: u64) -> StdResult<String, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_i64<E: DeError>(self, value: i64) -> StdResult<String, E> {
+            if value < 0 {
+                return Err(E::invalid_value(Unexpected::Signed(value), &self));
+            }
+
+            Ok(value.to_string())
+        }
+    }
+
+    deserializer.deserialize_any(GuildIdVisitor)
+}
+
 /// Utility function to serialize Option<u64> with no present value as 0 instead of null
 fn serialize_option_u64<S: Serializer>(option: &Option<u64>, s: S) -> StdResult<S::Ok, S::Error> {
-    let value = match *option {
-        Some(value) => value,
-        None => 0,
-    };
-
-    s.serialize_u64(value)
+    s.serialize_u64(option.unwrap_or_default())
 }
 
 macro_rules! impl_stuff_for_model {
@@ -910,9 +2211,11 @@ macro_rules! impl_stuff_for_model {
 
 impl_stuff_for_model! {
     Destroy,
+    Equalizer,
     Pause,
     Play,
     PlayerUpdate,
+    Ready,
     Seek,
     Stats,
     Stop,
@@ -920,12 +2223,36 @@ impl_stuff_for_model! {
     Volume
 }
 
+macro_rules! impl_into_ws_message {
+    ($($model: ident),*) => {
+        $(
+            impl IntoWebSocketMessage for $model {
+                fn into_ws_message(self) -> Result<WireMessage> {
+                    Ok(WireMessage(serde_json::to_string(&self)?))
+                }
+            }
+        )*
+    };
+}
+
+impl_into_ws_message! {
+    Destroy,
+    Equalizer,
+    OutgoingMessage,
+    Pause,
+    Play,
+    Seek,
+    Stop,
+    VoiceUpdate,
+    Volume
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json;
     use super::*;
 
-    static PLAYER_UPDATE: &'static str = r#"{
+    static PLAYER_UPDATE: &str = r#"{
   "guildId": "1",
   "op": "playerUpdate",
   "state": {
@@ -934,16 +2261,22 @@ mod tests {
   }
 }"#;
 
-    static STATS: &'static str = r#"{
+    static READY: &str = r#"{
+  "op": "ready",
+  "resumed": false,
+  "sessionId": "la3kfsdf5eafe848"
+}"#;
+
+    static STATS: &str = r#"{
   "cpu": {
     "cores": 4,
     "systemLoad": 0.022558908466914995,
     "lavalinkLoad": 0.003833333333333
   },
   "frameStats": {
-    "sent": 3000.0,
-    "nulled": 0.0,
-    "deficit": 0.0
+    "sent": 3000,
+    "nulled": 0,
+    "deficit": 0
   },
   "memory": {
     "allocated": 187695104,
@@ -957,7 +2290,7 @@ mod tests {
   "op": "stats"
 }"#;
 
-    static TRACK_END: &'static str = r#"{
+    static TRACK_END: &str = r#"{
   "op": "event",
   "reason": "FINISHED",
   "type": "TrackEndEvent",
@@ -967,7 +2300,19 @@ mod tests {
 
     #[test]
     fn test_track_end_event() {
-        serde_json::from_str::<EventTrackEnd>(&TRACK_END).unwrap();
+        serde_json::from_str::<EventTrackEnd>(TRACK_END).unwrap();
+    }
+
+    #[test]
+    fn test_track_start_event() {
+        let payload = r#"{"op":"event","type":"TrackStartEvent","guildId":"1","track":"foo"}"#;
+
+        match IncomingMessage::parse(payload, ParseMode::Strict).unwrap() {
+            IncomingMessage::Event(event @ Event::TrackStart(_)) => {
+                assert_eq!(event.guild_id(), "1");
+            },
+            other => panic!("unexpected message: {:?}", other),
+        }
     }
 
     #[test]
@@ -975,6 +2320,110 @@ mod tests {
         serde_json::from_str::<IncomingMessage>(STATS).unwrap();
     }
 
+    #[test]
+    fn test_parse_modes() {
+        let unknown_op = r#"{"op":"lyrics","guildId":"1"}"#;
+        let unknown_event = r#"{"op":"event","type":"FooEvent","guildId":"1"}"#;
+
+        for payload in &[unknown_op, unknown_event] {
+            let message = IncomingMessage::parse(payload, ParseMode::Lenient)
+                .unwrap();
+            assert!(message.is_unknown());
+        }
+
+        match IncomingMessage::parse(unknown_op, ParseMode::Strict) {
+            Err(Error::UnknownOpcode(ref op)) if op == "lyrics" => {},
+            other => panic!("parsed as {:?}", other),
+        }
+        match IncomingMessage::parse(unknown_event, ParseMode::Strict) {
+            Err(Error::UnknownEvent(ref kind)) if kind == "FooEvent" => {},
+            other => panic!("parsed as {:?}", other),
+        }
+
+        match IncomingMessage::parse(TRACK_END, ParseMode::Strict).unwrap() {
+            IncomingMessage::Event(Event::TrackEnd(_)) => {},
+            other => panic!("parsed as {:?}", other),
+        }
+        assert!(IncomingMessage::parse(
+            r#"{"op":"stats"}"#,
+            ParseMode::Lenient,
+        ).is_err());
+    }
+
+    #[test]
+    fn test_filters_equalizer() {
+        let equalizer = Equalizer::new("1", vec![Band::new(3, -0.1).unwrap()]);
+        let filters = Filters::from(equalizer.clone());
+
+        let value = serde_json::to_value(&filters).unwrap();
+        assert_eq!(value["equalizer"][0]["band"], 3);
+        assert!(value.get("volume").is_none());
+        assert_eq!(filters.to_equalizer("1").bands, equalizer.bands);
+
+        let filters = serde_json::from_str::<Filters>(
+            r#"{"volume":0.5,"timescale":{"speed":1.2}}"#,
+        ).unwrap();
+        assert!(filters.equalizer_bands().is_empty());
+        assert_eq!(filters.other["timescale"]["speed"], 1.2);
+        assert_eq!(
+            serde_json::to_value(&filters).unwrap(),
+            serde_json::json!({"volume": 0.5, "timescale": {"speed": 1.2}}),
+        );
+    }
+
+    #[test]
+    fn test_filters_timescale() {
+        let mut filters = serde_json::from_str::<Filters>(
+            r#"{"timescale":{"speed":1.2,"rate":1.0}}"#,
+        ).unwrap();
+        filters.set_pitch(12.0);
+        filters.set_nightcore(true);
+        assert_eq!(
+            filters.other["timescale"],
+            serde_json::json!({"speed": 1.2, "pitch": 2.0, "rate": 1.25}),
+        );
+
+        filters.set_nightcore(false);
+        assert_eq!(filters.other["timescale"]["rate"], 1.0);
+
+        // non-finite values can't be sent, so they're ignored
+        filters.set_speed(f64::NAN);
+        assert_eq!(filters.other["timescale"]["speed"], 1.2);
+    }
+
+    #[test]
+    fn test_numeric_guild_id() {
+        let update = serde_json::from_str::<PlayerUpdate>(
+            r#"{"guildId":381880193251409931,"op":"playerUpdate","state":{"time":1}}"#,
+        ).unwrap();
+        assert_eq!(update.guild_id, "381880193251409931");
+
+        let message = IncomingMessage::parse(
+            r#"{"guildId":1,"op":"event","type":"TrackEndEvent","reason":"FINISHED","track":"foo"}"#,
+            ParseMode::Strict,
+        ).unwrap();
+        match message {
+            IncomingMessage::Event(event) => assert_eq!(event.guild_id(), "1"),
+            other => panic!("parsed as {:?}", other),
+        }
+
+        assert!(serde_json::from_str::<EventTrackEnd>(
+            r#"{"guildId":-1,"op":"event","reason":"FINISHED","track":"foo"}"#,
+        ).is_err());
+    }
+
+    #[test]
+    fn test_ready_deser() {
+        match serde_json::from_str::<IncomingMessage>(READY).unwrap() {
+            IncomingMessage::Ready(ready) => {
+                assert_eq!(ready.opcode(), Opcode::Ready);
+                assert!(!ready.resumed);
+                assert_eq!(ready.session_id, "la3kfsdf5eafe848");
+            },
+            other => panic!("parsed as {:?}", other),
+        }
+    }
+
     #[test]
     fn test_stats_deser() {
         let stats = serde_json::from_str::<Stats>(STATS).unwrap();
@@ -982,6 +2431,56 @@ mod tests {
         assert_eq!(serde_json::to_string_pretty(&stats).unwrap(), STATS);
     }
 
+    #[test]
+    fn test_unknown_fields_kept() {
+        let payload = r#"{"op":"playerUpdate","guildId":"1","region":"eu","state":{"time":1,"position":2,"codec":"opus"}}"#;
+
+        let message = IncomingMessage::parse(payload, ParseMode::Strict).unwrap();
+        match message {
+            IncomingMessage::PlayerUpdate(ref update) => {
+                assert_eq!(update.extra["region"], "eu");
+                assert_eq!(update.state.extra["codec"], "opus");
+            },
+            ref other => panic!("parsed as {:?}", other),
+        }
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::from_str::<Value>(payload).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_play_volume_ser() {
+        let mut play = Play::new("1", "foo", None, None);
+        let value = serde_json::to_value(&play).unwrap();
+        assert!(value.get("volume").is_none());
+
+        play.volume = Some(50);
+        let value = serde_json::to_value(&play).unwrap();
+        assert_eq!(value["volume"], 50);
+    }
+
+    #[test]
+    fn test_stats_frames_negative_deficit() {
+        let frames = serde_json::from_str::<StatsFrames>(
+            r#"{"sent":3010,"nulled":0,"deficit":-10}"#,
+        ).unwrap();
+
+        assert_eq!(frames.expected_frame_count(), 3000);
+        assert!(frames.loss_ratio() < 0.0);
+    }
+
+    #[test]
+    fn test_durations() {
+        let seek = Seek::new_duration("1", Duration::from_millis(30_500));
+        assert_eq!(seek.position, 30_500);
+        assert_eq!(seek.position(), Duration::from_millis(30_500));
+
+        let state = PlayerUpdateState::new(1537848743531, -1);
+        assert_eq!(state.position(), Some(Duration::from_millis(0)));
+    }
+
     #[test]
     fn test_player_update_deser() {
         let update = serde_json::from_str::<PlayerUpdate>(
@@ -994,4 +2493,106 @@ mod tests {
             PLAYER_UPDATE,
         );
     }
+
+    #[test]
+    fn test_event_track_object() {
+        let payload = r#"{
+            "op": "event",
+            "type": "TrackEndEvent",
+            "guildId": "1",
+            "reason": "finished",
+            "track": {
+                "encoded": "QAAA",
+                "info": {},
+                "userData": { "requester": "2" }
+            }
+        }"#;
+
+        let message = IncomingMessage::parse(payload, ParseMode::Strict).unwrap();
+
+        match message {
+            IncomingMessage::Event(Event::TrackEnd(ref event)) => {
+                assert_eq!(event.track, "QAAA");
+                assert_eq!(event.user_data.as_ref().unwrap()["requester"], "2");
+                assert!(event.track_details.as_ref().unwrap().contains_key("info"));
+            },
+            ref other => panic!("unexpected message: {:?}", other),
+        }
+
+        // the track is serialized as the object it was received as
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::from_str::<Value>(payload).unwrap(),
+        );
+
+        let event = serde_json::from_str::<EventTrackEnd>(TRACK_END).unwrap();
+        assert!(event.user_data.is_none());
+        assert!(event.track_details.is_none());
+        assert!(!serde_json::to_string(&event).unwrap().contains("userData"));
+    }
+
+    #[test]
+    fn test_from_slice() {
+        let message = IncomingMessage::from_slice(
+            PLAYER_UPDATE.as_bytes(),
+            ParseMode::Strict,
+        ).unwrap();
+        match message {
+            IncomingMessage::PlayerUpdate(update) => {
+                assert_eq!(update.guild_id, "1");
+            },
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        // the snippet is cut before the multi-byte character at its end
+        let mut payload = vec![b'{'; MalformedMessage::SNIPPET_LENGTH - 1];
+        payload.extend_from_slice("é and more".as_bytes());
+
+        match IncomingMessage::from_slice(&payload, ParseMode::Lenient) {
+            Err(Error::MalformedMessage(why)) => {
+                assert_eq!(why.op(), None);
+                assert_eq!(
+                    why.snippet().len(),
+                    MalformedMessage::SNIPPET_LENGTH - 1,
+                );
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_into_ws_message() {
+        let stop = Stop::new("1");
+        let expected = serde_json::to_string(&stop).unwrap();
+
+        assert_eq!(stop.clone().into_ws_message().unwrap().as_str(), expected);
+        assert_eq!(
+            String::from(OutgoingMessage::Stop(stop).into_ws_message().unwrap()),
+            expected,
+        );
+    }
+
+    #[test]
+    fn test_stats_delta() {
+        let previous = Stats::builder()
+            .players(5)
+            .playing_players(2)
+            .frames(Some(StatsFrames::new(3000, 0, 0)))
+            .uptime(60_000)
+            .build();
+        let current = Stats::builder()
+            .players(4)
+            .playing_players(0)
+            .uptime(1_000)
+            .build();
+
+        let delta = current.delta(&previous);
+        assert_eq!(delta.players, -1);
+        assert_eq!(delta.playing_players, -2);
+        assert!(delta.restarted);
+        assert_eq!(delta.uptime, -59_000);
+        assert_eq!(delta.frames.unwrap().sent, -3000);
+
+        assert!(current.delta(&current).frames.is_none());
+    }
 }
@@ -1,15 +1,29 @@
 //! A collection of opcodes for use between the LavaLink client and server.
 
+use serde::de::{Deserialize, Deserializer, Error as DeError, Visitor};
+use serde::{Serialize, Serializer};
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use std::str::FromStr;
-use std::string::ToString;
 
 /// An opcode used to indicate the type of message received.
 ///
-/// Note that although serde's `Deserialize` and `Serialize` are derived on this
-/// type, it may be preferable to use the `FromStr` and `ToString`
-/// implementations for performance in some cases.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
+/// Note that although serde's `Deserialize` and `Serialize` are implemented
+/// on this type, it may be preferable to use the `FromStr` implementation and
+/// [`as_str`] for performance in some cases.
+///
+/// Deserializing an opcode that isn't known produces [`Opcode::Unknown`]
+/// rather than an error, so that messages with new opcodes don't fail to
+/// parse. [`parse_lossy`] does the same for strings, while `FromStr` and
+/// `TryFrom<&str>` return an [`UnknownOpcodeError`].
+///
+/// [`as_str`]: #method.as_str
+/// [`parse_lossy`]: #method.parse_lossy
+/// [`Opcode::Unknown`]: #variant.Unknown
+/// [`UnknownOpcodeError`]: struct.UnknownOpcodeError.html
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 pub enum Opcode {
     /// Destroys a player for a guild.
     ///
@@ -35,6 +49,10 @@ pub enum Opcode {
     ///
     /// This is sent by the server to the client.
     PlayerUpdate,
+    /// Indicates that the connection to a Lavalink v4 node is ready.
+    ///
+    /// This is sent by the server to the client.
+    Ready,
     /// Makes the player seek to a position of the track.
     ///
     /// This is sent by the client to the server.
@@ -48,7 +66,9 @@ pub enum Opcode {
     /// This is sent by the client to the server.
     Stop,
     /// An unknown opcode.
-    Unknown,
+    ///
+    /// This contains the opcode as it was received.
+    Unknown(String),
     /// A voice state update received from Discord to be forwarded.
     ///
     /// This is sent by the client to the server.
@@ -60,29 +80,100 @@ pub enum Opcode {
     Volume,
 }
 
-impl ToString for Opcode {
-    fn to_string(&self) -> String {
+impl Opcode {
+    /// Parses an opcode from its name, returning [`Opcode::Unknown`] if it
+    /// isn't known.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::opcodes::Opcode;
+    ///
+    /// assert_eq!(Opcode::parse_lossy("stats"), Opcode::Stats);
+    /// assert_eq!(
+    ///     Opcode::parse_lossy("lyrics"),
+    ///     Opcode::Unknown("lyrics".to_owned()),
+    /// );
+    /// ```
+    ///
+    /// [`Opcode::Unknown`]: #variant.Unknown
+    pub fn parse_lossy(op: impl AsRef<str>) -> Self {
+        let op = op.as_ref();
+
+        op.parse().unwrap_or_else(|_| Opcode::Unknown(op.to_owned()))
+    }
+
+    /// Returns the name of the opcode as sent over the wire.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::opcodes::Opcode;
+    ///
+    /// assert_eq!(Opcode::PlayerUpdate.as_str(), "playerUpdate");
+    /// assert_eq!(Opcode::Unknown("lyrics".to_owned()).as_str(), "lyrics");
+    /// ```
+    pub fn as_str(&self) -> &str {
         use self::Opcode::*;
 
-        match *self {
+        match self {
             Destroy => "destroy",
             Equalizer => "equalizer",
             Event => "event",
             Pause => "pause",
             Play => "play",
             PlayerUpdate => "playerUpdate",
+            Ready => "ready",
             Seek => "seek",
             Stats => "stats",
             Stop => "stop",
-            Unknown => "unknown",
+            Unknown(op) => op,
             VoiceUpdate => "voiceUpdate",
             Volume => "volume",
-        }.to_owned()
+        }
+    }
+}
+
+impl Display for Opcode {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Opcode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// An error returned when parsing an opcode that isn't known.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct UnknownOpcodeError {
+    op: String,
+}
+
+impl UnknownOpcodeError {
+    /// Returns the opcode that isn't known.
+    pub fn as_str(&self) -> &str {
+        &self.op
+    }
+
+    /// Returns the opcode that isn't known, consuming the error.
+    pub fn into_string(self) -> String {
+        self.op
+    }
+}
+
+impl Display for UnknownOpcodeError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "Unknown opcode: {:?}", self.op)
     }
 }
 
+impl StdError for UnknownOpcodeError {}
+
 impl FromStr for Opcode {
-    type Err = Opcode;
+    type Err = UnknownOpcodeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use self::Opcode::*;
@@ -97,9 +188,67 @@ impl FromStr for Opcode {
             "seek" => Seek,
             "volume" => Volume,
             "playerUpdate" => PlayerUpdate,
+            "ready" => Ready,
             "stats" => Stats,
             "event" => Event,
-            _ => return Err(Unknown),
+            other => return Err(UnknownOpcodeError {
+                op: other.to_owned(),
+            }),
         })
     }
 }
+
+impl<'a> TryFrom<&'a str> for Opcode {
+    type Error = UnknownOpcodeError;
+
+    fn try_from(op: &'a str) -> Result<Self, Self::Error> {
+        op.parse()
+    }
+}
+
+impl<'de> Deserialize<'de> for Opcode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(OpcodeVisitor)
+    }
+}
+
+struct OpcodeVisitor;
+
+impl<'de> Visitor<'de> for OpcodeVisitor {
+    type Value = Opcode;
+
+    fn expecting(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("an opcode string")
+    }
+
+    fn visit_str<E: DeError>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(Opcode::parse_lossy(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use std::convert::TryFrom;
+    use super::Opcode;
+
+    #[test]
+    fn test_unknown_deser() {
+        let op = serde_json::from_str::<Opcode>(r#""lyrics""#).unwrap();
+        assert_eq!(op, Opcode::Unknown("lyrics".to_owned()));
+        assert_eq!(serde_json::to_string(&op).unwrap(), r#""lyrics""#);
+
+        let op = serde_json::from_str::<Opcode>(r#""playerUpdate""#).unwrap();
+        assert_eq!(op, Opcode::PlayerUpdate);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("voiceUpdate".parse::<Opcode>(), Ok(Opcode::VoiceUpdate));
+        assert_eq!(Opcode::try_from("event"), Ok(Opcode::Event));
+
+        let err = Opcode::try_from("lyrics").unwrap_err();
+        assert_eq!(err.as_str(), "lyrics");
+        assert_eq!(err.to_string(), r#"Unknown opcode: "lyrics""#);
+    }
+}
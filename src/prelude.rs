@@ -1,2 +1,56 @@
-pub use crate::{Error, Result};
-pub use serde_json::Value;
+//! A collection of the most commonly used types, to be glob imported.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use lavalink::prelude::*;
+//!
+//! let play = Play::new("381880193251409931", "QAAA...", None, None);
+//! let message = OutgoingMessage::Play(play);
+//! ```
+//!
+//! The owned REST clients of each enabled backend are exported as
+//! `HyperRestClient` and `ReqwestRestClient`, and their requester traits are
+//! imported anonymously so that their methods are in scope.
+
+pub use crate::decoder::DecodedTrack;
+pub use crate::model::{
+    Band,
+    Destroy,
+    Equalizer,
+    Event,
+    Filters,
+    IncomingMessage,
+    IntoWebSocketMessage,
+    OutgoingMessage,
+    ParseMode,
+    Pause,
+    Play,
+    PlayerUpdate,
+    Seek,
+    Stats,
+    Stop,
+    VoiceUpdate,
+    Volume,
+    WireMessage,
+};
+pub use crate::opcodes::Opcode;
+pub use crate::rest::{
+    Load,
+    LoadedTrack,
+    LoadedTrackInfo,
+    LoadType,
+    PlayerUpdateRequest,
+};
+pub use crate::{Error, ErrorKind, Result, Secret};
+
+#[cfg(feature = "hyper")]
+pub use crate::rest::hyper::{
+    LavalinkRestRequester as _,
+    RestClient as HyperRestClient,
+};
+#[cfg(feature = "reqwest")]
+pub use crate::rest::reqwest::{
+    LavalinkRestRequester as _,
+    RestClient as ReqwestRestClient,
+};
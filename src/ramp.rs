@@ -0,0 +1,310 @@
+//! Utilities for changing a player's filters gradually rather than all at
+//! once, such as to speed a track up for a nightcore effect without an abrupt
+//! jump.
+//!
+//! A [`Ramp`] produces the intermediate [`Filters`] between two settings, each
+//! with the time after the start of the ramp it should be sent at. Sending
+//! them is up to the caller, as a `filters` message or with a REST client's
+//! `update_player`.
+//!
+//! # Examples
+//!
+//! Speed a track up from normal speed to 1.3x over five seconds:
+//!
+//! ```rust
+//! use lavalink::model::Filters;
+//! use lavalink::ramp::Ramp;
+//! use std::time::Duration;
+//!
+//! fn timescale(speed: f64) -> Filters {
+//!     let mut filters = Filters::new();
+//!     filters.other.insert(
+//!         "timescale".to_owned(),
+//!         serde_json::json!({ "speed": speed, "pitch": 1.0, "rate": 1.0 }),
+//!     );
+//!
+//!     filters
+//! }
+//!
+//! let duration = Duration::from_secs(5);
+//!
+//! for step in Ramp::new(timescale(1.0), timescale(1.3), duration) {
+//!     // wait until `step.at` after starting, then send `step.filters`
+//! }
+//! ```
+//!
+//! [`Filters`]: ../model/struct.Filters.html
+//! [`Ramp`]: struct.Ramp.html
+
+use crate::model::{Band, Filters};
+use serde_json::{Map, Number, Value};
+use std::convert::TryFrom;
+use std::time::Duration;
+
+/// The default time between the steps of a ramp.
+pub const DEFAULT_TICK: Duration = Duration::from_millis(250);
+
+/// The shortest time allowed between the steps of a ramp.
+///
+/// Shorter ticks are raised to this, as nodes apply filters in batches of
+/// audio frames and sending updates faster only adds load.
+pub const MIN_TICK: Duration = Duration::from_millis(100);
+
+/// Interpolates between two sets of filters.
+///
+/// `progress` is clamped to the range of `0.0`, returning `from`, to `1.0`,
+/// returning `to`.
+///
+/// Numbers present in both sets are interpolated linearly, including the
+/// values of other filters such as `timescale`. A missing volume is treated
+/// as `1.0` and a missing equalizer band as a gain of `0.0`. Other filters
+/// only in `to` are applied immediately, while any other value that can't be
+/// interpolated keeps its value from `from` until the end of the ramp.
+pub fn interpolate(from: &Filters, to: &Filters, progress: f64) -> Filters {
+    if progress <= 0.0 {
+        return from.clone();
+    } else if progress >= 1.0 {
+        return to.clone();
+    }
+
+    let volume = match (from.volume, to.volume) {
+        (None, None) => None,
+        (from, to) => {
+            Some(lerp(from.unwrap_or(1.0), to.unwrap_or(1.0), progress))
+        },
+    };
+
+    let equalizer = match (&from.equalizer, &to.equalizer) {
+        (None, None) => None,
+        _ => Some(interpolate_bands(
+            from.equalizer_bands(),
+            to.equalizer_bands(),
+            progress,
+        )),
+    };
+
+    Filters {
+        equalizer,
+        other: interpolate_map(&from.other, &to.other, progress),
+        volume,
+    }
+}
+
+fn lerp(from: f64, to: f64, progress: f64) -> f64 {
+    from + (to - from) * progress
+}
+
+fn interpolate_bands(from: &[Band], to: &[Band], progress: f64) -> Vec<Band> {
+    let gain = |bands: &[Band], band: u8| {
+        bands.iter().find(|b| b.band == band).map_or(0.0, |b| b.gain)
+    };
+
+    let mut numbers = from.iter().chain(to).map(|b| b.band).collect::<Vec<_>>();
+    numbers.sort_unstable();
+    numbers.dedup();
+
+    numbers.into_iter().map(|band| Band {
+        band,
+        gain: lerp(gain(from, band), gain(to, band), progress),
+    }).collect()
+}
+
+fn interpolate_map(
+    from: &Map<String, Value>,
+    to: &Map<String, Value>,
+    progress: f64,
+) -> Map<String, Value> {
+    let mut map = from.clone();
+
+    for (key, to) in to {
+        let value = match map.get(key) {
+            Some(from) => interpolate_value(from, to, progress),
+            None => to.clone(),
+        };
+
+        map.insert(key.clone(), value);
+    }
+
+    map
+}
+
+fn interpolate_value(from: &Value, to: &Value, progress: f64) -> Value {
+    match (from, to) {
+        (Value::Number(a), Value::Number(b)) => {
+            match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => Number::from_f64(lerp(a, b, progress))
+                    .map_or_else(|| from.clone(), Value::Number),
+                _ => from.clone(),
+            }
+        },
+        (Value::Object(a), Value::Object(b)) => {
+            Value::Object(interpolate_map(a, b, progress))
+        },
+        _ => from.clone(),
+    }
+}
+
+/// An iterator of the intermediate filters between two sets of filters.
+///
+/// The last step is always exactly the target filters, at the end of the
+/// ramp's duration.
+#[derive(Clone, Debug)]
+pub struct Ramp {
+    duration: Duration,
+    from: Filters,
+    step: u32,
+    steps: u32,
+    tick: Duration,
+    to: Filters,
+}
+
+impl Ramp {
+    /// Creates a ramp between two sets of filters over a duration, with
+    /// steps every [`DEFAULT_TICK`].
+    ///
+    /// [`DEFAULT_TICK`]: constant.DEFAULT_TICK.html
+    pub fn new(from: Filters, to: Filters, duration: Duration) -> Self {
+        Self {
+            duration,
+            from,
+            step: 0,
+            steps: 0,
+            tick: DEFAULT_TICK,
+            to,
+        }.tick(DEFAULT_TICK)
+    }
+
+    /// Sets the time between steps.
+    ///
+    /// This is raised to [`MIN_TICK`] if it is shorter.
+    ///
+    /// [`MIN_TICK`]: constant.MIN_TICK.html
+    pub fn tick(mut self, tick: Duration) -> Self {
+        self.tick = tick.max(MIN_TICK);
+
+        let (duration, tick) = (self.duration.as_nanos(), self.tick.as_nanos());
+        let ticks = duration / tick;
+        let partial = duration % tick != 0;
+        self.steps = u32::try_from(ticks)
+            .unwrap_or(u32::MAX)
+            .saturating_add(partial as u32)
+            .max(1);
+
+        self
+    }
+}
+
+/// A step of a [`Ramp`].
+///
+/// [`Ramp`]: struct.Ramp.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct RampStep {
+    /// The time after the start of the ramp to apply the filters at.
+    pub at: Duration,
+    /// The filters to apply.
+    pub filters: Filters,
+}
+
+impl Iterator for Ramp {
+    type Item = RampStep;
+
+    fn next(&mut self) -> Option<RampStep> {
+        if self.step >= self.steps {
+            return None;
+        }
+
+        self.step += 1;
+
+        // the last step may be past the largest duration, if the tick is
+        // close to it
+        let at = self.tick
+            .checked_mul(self.step)
+            .map_or(self.duration, |at| at.min(self.duration));
+        let progress = if self.step == self.steps {
+            1.0
+        } else {
+            at.as_secs_f64() / self.duration.as_secs_f64()
+        };
+
+        Some(RampStep {
+            at,
+            filters: interpolate(&self.from, &self.to, progress),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.steps - self.step) as usize;
+
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Band, Filters};
+    use serde_json::json;
+    use std::time::Duration;
+    use super::{interpolate, Ramp};
+
+    fn filters(value: serde_json::Value) -> Filters {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let from = filters(json!({
+            "equalizer": [{ "band": 0, "gain": 0.2 }],
+            "timescale": { "speed": 1.0, "pitch": 1.0 },
+        }));
+        let to = filters(json!({
+            "equalizer": [{ "band": 1, "gain": 0.4 }],
+            "timescale": { "speed": 2.0, "pitch": 1.0 },
+            "volume": 0.5,
+        }));
+
+        let half = interpolate(&from, &to, 0.5);
+        assert_eq!(half.volume, Some(0.75));
+        assert_eq!(half.equalizer.unwrap(), vec![
+            Band::new(0, 0.1).unwrap(),
+            Band::new(1, 0.2).unwrap(),
+        ]);
+        assert_eq!(half.other["timescale"], json!({ "speed": 1.5, "pitch": 1.0 }));
+
+        assert_eq!(interpolate(&from, &to, -1.0), from);
+        assert_eq!(interpolate(&from, &to, 1.0), to);
+    }
+
+    #[test]
+    fn test_ramp() {
+        let from = filters(json!({ "volume": 1.0 }));
+        let to = filters(json!({ "volume": 2.0 }));
+
+        let steps = Ramp::new(from.clone(), to.clone(), Duration::from_millis(1100))
+            .tick(Duration::from_millis(500))
+            .collect::<Vec<_>>();
+        let times = steps.iter().map(|step| step.at.as_millis()).collect::<Vec<_>>();
+        assert_eq!(times, vec![500, 1000, 1100]);
+        assert_eq!(steps.last().unwrap().filters, to);
+
+        // ticks are limited, and an empty duration still applies the target
+        let ramp = Ramp::new(from.clone(), to.clone(), Duration::from_secs(1))
+            .tick(Duration::from_millis(1));
+        assert_eq!(ramp.count(), 10);
+
+        let steps = Ramp::new(from, to.clone(), Duration::from_secs(0))
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].filters, to);
+
+        // a duration with more ticks than fit in a u32 doesn't wrap around
+        let ramp = Ramp::new(Filters::new(), to.clone(), Duration::from_secs(u64::MAX));
+        assert_eq!(ramp.size_hint().0, u32::MAX as usize);
+
+        // nor does the time of a step that is past the largest duration
+        let steps = Ramp::new(Filters::new(), to, Duration::from_secs(u64::MAX))
+            .tick(Duration::from_secs(u64::MAX / 2 + 1))
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[1].at, Duration::from_secs(u64::MAX));
+    }
+}
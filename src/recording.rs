@@ -0,0 +1,275 @@
+//! Recording the raw frames received from a node, and replaying them later,
+//! to reproduce bugs offline.
+//!
+//! A [`Recorder`] writes each frame as a line of JSON with the time it was
+//! received, and a [`Replayer`] reads them back in order. Replayed frames can
+//! be parsed and handled the same way as live ones, optionally paced to the
+//! original timing with [`Replayer::paced`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn main() -> lavalink::Result<()> {
+//! use lavalink::model::{IncomingMessage, ParseMode};
+//! use lavalink::recording::{Recorder, Replayer};
+//!
+//! let mut recorder = Recorder::new(Vec::new());
+//! recorder.record(r#"{"op":"playerUpdate","guildId":"1","state":{"time":1}}"#)?;
+//! let recording = recorder.into_inner();
+//!
+//! for frame in Replayer::new(recording.as_slice()) {
+//!     match frame?.parse(ParseMode::Lenient)? {
+//!         IncomingMessage::PlayerUpdate(update) => {
+//!             assert_eq!(update.guild_id, "1");
+//!         },
+//!         other => panic!("unexpected message: {:?}", other),
+//!     }
+//! }
+//! #     Ok(())
+//! # }
+//! ```
+//!
+//! [`Recorder`]: struct.Recorder.html
+//! [`Replayer`]: struct.Replayer.html
+//! [`Replayer::paced`]: struct.Replayer.html#method.paced
+
+use crate::model::{IncomingMessage, ParseMode};
+use crate::Result;
+use serde_json;
+use std::io::{BufRead, Lines, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A frame received from a node, along with when it was received.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RecordedFrame {
+    /// The number of milliseconds after the start of the recording at which
+    /// the frame was received.
+    pub at: u64,
+    /// The payload of the frame.
+    pub frame: String,
+}
+
+impl RecordedFrame {
+    /// Returns the time after the start of the recording at which the frame
+    /// was received.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.at)
+    }
+
+    /// Parses the frame as a message from the node.
+    ///
+    /// This is the same as [`IncomingMessage::parse`].
+    ///
+    /// [`IncomingMessage::parse`]: ../model/enum.IncomingMessage.html#method.parse
+    pub fn parse(&self, mode: ParseMode) -> Result<IncomingMessage> {
+        IncomingMessage::parse(&self.frame, mode)
+    }
+}
+
+/// A writer of received frames, one JSON object per line.
+///
+/// Times are counted from when the recorder was created.
+#[derive(Debug)]
+pub struct Recorder<W> {
+    started: Instant,
+    writer: W,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Creates a recorder writing to a writer, such as a file.
+    ///
+    /// Frames are written as they are recorded, so wrap the writer in a
+    /// `BufWriter` to reduce the number of writes.
+    pub fn new(writer: W) -> Self {
+        Self {
+            started: Instant::now(),
+            writer,
+        }
+    }
+
+    /// Records a frame received now.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if writing the frame failed.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn record(&mut self, frame: impl Into<String>) -> Result<()> {
+        let at = self.started.elapsed().as_millis() as u64;
+
+        self.write(&RecordedFrame {
+            at,
+            frame: frame.into(),
+        })
+    }
+
+    /// Records a frame with its time already set, such as when copying
+    /// frames from another recording.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if writing the frame failed.
+    ///
+    /// [`Error::Io`]: ../enum.Error.html#variant.Io
+    pub fn write(&mut self, frame: &RecordedFrame) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, frame)?;
+        self.writer.write_all(b"\n")?;
+
+        Ok(())
+    }
+
+    /// Returns the writer, consuming the recorder.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// An iterator of the frames of a recording, in the order they were
+/// recorded.
+///
+/// Empty lines are skipped.
+#[derive(Debug)]
+pub struct Replayer<R> {
+    lines: Lines<R>,
+}
+
+impl<R: BufRead> Replayer<R> {
+    /// Creates a replayer reading a recording from a reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+        }
+    }
+
+    /// Paces the frames to the times they were recorded at, divided by a
+    /// speed.
+    ///
+    /// Each frame is returned once its time has passed since the first call
+    /// to `next`, sleeping the current thread until then. A speed of `2.0`
+    /// replays twice as fast as the recording, while a speed that isn't
+    /// positive and finite doesn't wait at all.
+    pub fn paced(self, speed: f64) -> Paced<R> {
+        Paced {
+            replayer: self,
+            speed,
+            started: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Replayer<R> {
+    type Item = Result<RecordedFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(why) => return Some(Err(why.into())),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(serde_json::from_str(&line).map_err(From::from));
+        }
+    }
+}
+
+/// A [`Replayer`] paced to the times the frames were recorded at.
+///
+/// This is created with [`Replayer::paced`].
+///
+/// [`Replayer`]: struct.Replayer.html
+/// [`Replayer::paced`]: struct.Replayer.html#method.paced
+#[derive(Debug)]
+pub struct Paced<R> {
+    replayer: Replayer<R>,
+    speed: f64,
+    started: Option<Instant>,
+}
+
+impl<R: BufRead> Iterator for Paced<R> {
+    type Item = Result<RecordedFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let frame = match self.replayer.next()? {
+            Ok(frame) => frame,
+            Err(why) => return Some(Err(why)),
+        };
+
+        if self.speed > 0.0 && self.speed.is_finite() {
+            let due = due(frame.elapsed(), self.speed);
+
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                thread::sleep(wait);
+            }
+        }
+
+        Some(Ok(frame))
+    }
+}
+
+/// Returns when a frame is due after the start of a replay at a speed.
+///
+/// A tiny speed can put the frame further away than a `Duration` can hold,
+/// in which case it is due at the largest `Duration`.
+fn due(elapsed: Duration, speed: f64) -> Duration {
+    let due = elapsed.as_secs_f64() / speed;
+
+    // every f64 below this bound has a whole number of seconds that fits a
+    // u64
+    if due < u64::MAX as f64 {
+        Duration::from_secs_f64(due)
+    } else {
+        Duration::from_secs(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{IncomingMessage, ParseMode};
+    use std::time::{Duration, Instant};
+    use super::{RecordedFrame, Recorder, Replayer};
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut recorder = Recorder::new(Vec::new());
+        recorder.record(r#"{"op":"ready","resumed":false,"sessionId":"a"}"#)
+            .unwrap();
+        recorder.write(&RecordedFrame {
+            at: 100,
+            frame: "not json".to_owned(),
+        }).unwrap();
+        let mut recording = recorder.into_inner();
+        recording.extend_from_slice(b"\n");
+
+        let frames = Replayer::new(recording.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].elapsed(), Duration::from_millis(100));
+
+        match frames[0].parse(ParseMode::Strict).unwrap() {
+            IncomingMessage::Ready(ready) => assert_eq!(ready.session_id, "a"),
+            other => panic!("unexpected message: {:?}", other),
+        }
+        assert!(frames[1].parse(ParseMode::Lenient).is_err());
+
+        let started = Instant::now();
+        let paced = Replayer::new(recording.as_slice()).paced(2.0).count();
+        assert_eq!(paced, 2);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_due() {
+        let elapsed = Duration::from_millis(100);
+
+        assert_eq!(super::due(elapsed, 2.0), Duration::from_millis(50));
+        assert_eq!(super::due(elapsed, 1e-300), Duration::from_secs(u64::MAX));
+        assert_eq!(super::due(Duration::from_secs(0), 1e-300), Duration::from_secs(0));
+    }
+}
@@ -0,0 +1,281 @@
+//! A framed protocol for forwarding messages to and from a process that hosts
+//! the node connections.
+//!
+//! Each frame is a [`RemoteMessage`] encoded as JSON, prefixed with its length
+//! in bytes as a big-endian `u32`. Frames can be sent over any byte stream,
+//! such as a TCP connection or a pipe, by writing them with [`write_frame`]
+//! and reading them on the other end with [`read_frame`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn main() -> lavalink::Result<()> {
+//! use lavalink::model::{OutgoingMessage, Pause};
+//! use lavalink::remote::{self, RemoteMessage};
+//!
+//! let message = RemoteMessage::Outgoing(OutgoingMessage::Pause(
+//!     Pause::new("381880193251409931", true),
+//! ));
+//!
+//! let mut stream = Vec::new();
+//! remote::write_frame(&mut stream, &message)?;
+//!
+//! let received = remote::read_frame(&mut stream.as_slice())?;
+//! assert!(received.is_some());
+//! #     Ok(())
+//! # }
+//! ```
+//!
+//! [`RemoteMessage`]: enum.RemoteMessage.html
+//! [`read_frame`]: fn.read_frame.html
+//! [`write_frame`]: fn.write_frame.html
+
+use byteorder::{BE, WriteBytesExt};
+use crate::model::{IncomingMessage, OutgoingMessage, ParseMode};
+use crate::opcodes::Opcode;
+use crate::{Error, Result};
+use serde_json::{self, Value, from_value};
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Read, Write};
+
+/// The largest frame that will be read, in bytes.
+///
+/// Larger frames are rejected rather than allocated, so a corrupt length
+/// prefix can't exhaust memory.
+pub const MAX_FRAME_LENGTH: u32 = 16 * 1024 * 1024;
+
+/// A message forwarded between the process connected to the nodes and a
+/// process controlling players.
+#[derive(Clone, Debug, Serialize)]
+#[serde(content = "message", rename_all = "camelCase", tag = "direction")]
+pub enum RemoteMessage {
+    /// A message received from a node, to be handled by the controlling
+    /// process.
+    Incoming(IncomingMessage),
+    /// A message to be sent to a node by the connected process.
+    Outgoing(OutgoingMessage),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Direction {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Deserialize)]
+struct RawFrame {
+    direction: Direction,
+    message: Value,
+}
+
+/// Reads a single frame from a stream.
+///
+/// Returns `None` if the stream ended before the start of a frame.
+///
+/// Incoming messages are parsed as with [`IncomingMessage::parse`] in
+/// [`ParseMode::Lenient`], so an unknown message is read as
+/// [`IncomingMessage::Unknown`].
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if reading from the stream failed, if the stream
+/// ended partway through a frame, or if the frame is longer than
+/// [`MAX_FRAME_LENGTH`].
+///
+/// Returns [`Error::Json`] if the frame is not a valid [`RemoteMessage`].
+///
+/// Returns [`Error::UnknownOpcode`] if an outgoing message has an opcode that
+/// isn't sent to nodes.
+///
+/// [`Error::Io`]: ../enum.Error.html#variant.Io
+/// [`Error::Json`]: ../enum.Error.html#variant.Json
+/// [`Error::UnknownOpcode`]: ../enum.Error.html#variant.UnknownOpcode
+/// [`IncomingMessage::Unknown`]: ../model/enum.IncomingMessage.html#variant.Unknown
+/// [`IncomingMessage::parse`]: ../model/enum.IncomingMessage.html#method.parse
+/// [`MAX_FRAME_LENGTH`]: constant.MAX_FRAME_LENGTH.html
+/// [`ParseMode::Lenient`]: ../model/enum.ParseMode.html#variant.Lenient
+/// [`RemoteMessage`]: enum.RemoteMessage.html
+pub fn read_frame(reader: &mut impl Read) -> Result<Option<RemoteMessage>> {
+    // the prefix is read by hand, so that a stream ending before it is told
+    // apart from one ending partway through it
+    let mut prefix = [0; 4];
+    let mut read = 0;
+
+    while read < prefix.len() {
+        match reader.read(&mut prefix[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(IoError::new(
+                    IoErrorKind::UnexpectedEof,
+                    "stream ended partway through the length of a frame",
+                ).into());
+            },
+            Ok(count) => read += count,
+            Err(ref why) if why.kind() == IoErrorKind::Interrupted => {},
+            Err(why) => return Err(why.into()),
+        }
+    }
+
+    let len = u32::from_be_bytes(prefix);
+
+    if len > MAX_FRAME_LENGTH {
+        return Err(IoError::new(
+            IoErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds the maximum length", len),
+        ).into());
+    }
+
+    let mut buf = vec![0; len as usize];
+    reader.read_exact(&mut buf)?;
+
+    let frame = serde_json::from_slice::<RawFrame>(&buf)?;
+
+    let message = match frame.direction {
+        // parsed like a message from a node, so that unknown messages are
+        // read back as they were forwarded
+        Direction::Incoming => RemoteMessage::Incoming(
+            IncomingMessage::parse_value(frame.message, ParseMode::Lenient)?,
+        ),
+        Direction::Outgoing => {
+            RemoteMessage::Outgoing(outgoing_from_value(frame.message)?)
+        },
+    };
+
+    Ok(Some(message))
+}
+
+// Outgoing messages are untagged and several share the same fields, such as
+// Destroy and Stop, so the variant is picked by the opcode.
+fn outgoing_from_value(value: Value) -> Result<OutgoingMessage> {
+    let op = value.get("op").cloned().unwrap_or(Value::Null);
+    let op = from_value::<Opcode>(op)?;
+
+    Ok(match op {
+        Opcode::Destroy => OutgoingMessage::Destroy(from_value(value)?),
+        Opcode::Equalizer => OutgoingMessage::Equalizer(from_value(value)?),
+        Opcode::Pause => OutgoingMessage::Pause(from_value(value)?),
+        Opcode::Play => OutgoingMessage::Play(from_value(value)?),
+        Opcode::Seek => OutgoingMessage::Seek(from_value(value)?),
+        Opcode::Stop => OutgoingMessage::Stop(from_value(value)?),
+        Opcode::VoiceUpdate => OutgoingMessage::VoiceUpdate(from_value(value)?),
+        Opcode::Volume => OutgoingMessage::Volume(from_value(value)?),
+        other => return Err(Error::UnknownOpcode(other.as_str().to_owned())),
+    })
+}
+
+/// Writes a single frame to a stream.
+///
+/// The stream is not flushed.
+///
+/// # Errors
+///
+/// Returns [`Error::Io`] if writing to the stream failed, or if the encoded
+/// message is longer than [`MAX_FRAME_LENGTH`].
+///
+/// Returns [`Error::Json`] if the message could not be serialized.
+///
+/// [`Error::Io`]: ../enum.Error.html#variant.Io
+/// [`Error::Json`]: ../enum.Error.html#variant.Json
+/// [`MAX_FRAME_LENGTH`]: constant.MAX_FRAME_LENGTH.html
+pub fn write_frame(
+    writer: &mut impl Write,
+    message: &RemoteMessage,
+) -> Result<()> {
+    let buf = serde_json::to_vec(message)?;
+
+    if buf.len() > MAX_FRAME_LENGTH as usize {
+        return Err(IoError::new(
+            IoErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds the maximum length", buf.len()),
+        ).into());
+    }
+
+    writer.write_u32::<BE>(buf.len() as u32)?;
+    writer.write_all(&buf)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{IncomingMessage, OutgoingMessage, ParseMode, PlayerUpdate, Stop};
+    use crate::Error;
+    use serde_json::Value;
+    use std::io::ErrorKind as IoErrorKind;
+    use super::{read_frame, write_frame, RemoteMessage, MAX_FRAME_LENGTH};
+
+    #[test]
+    fn test_frames() {
+        let mut stream = Vec::new();
+        write_frame(&mut stream, &RemoteMessage::Outgoing(
+            OutgoingMessage::Stop(Stop::new("1")),
+        )).unwrap();
+        write_frame(&mut stream, &RemoteMessage::Incoming(
+            IncomingMessage::PlayerUpdate(PlayerUpdate::new("1", 10, 20)),
+        )).unwrap();
+
+        let mut reader = stream.as_slice();
+
+        match read_frame(&mut reader).unwrap() {
+            Some(RemoteMessage::Outgoing(OutgoingMessage::Stop(stop))) => {
+                assert_eq!(stop.guild_id, "1");
+            },
+            other => panic!("unexpected frame: {:?}", other),
+        }
+        match read_frame(&mut reader).unwrap() {
+            Some(RemoteMessage::Incoming(IncomingMessage::PlayerUpdate(u))) => {
+                assert_eq!(u.state.position, Some(20));
+            },
+            other => panic!("unexpected frame: {:?}", other),
+        }
+        assert!(read_frame(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_incoming_frames() {
+        let payloads = [
+            r#"{"op":"lyrics","guildId":"1"}"#,
+            r#"{"op":"event","type":"TrackStartEvent","guildId":"1","track":{"encoded":"QAAA","info":{"title":"a"}}}"#,
+        ];
+
+        for payload in &payloads {
+            let message = IncomingMessage::parse(payload, ParseMode::Lenient).unwrap();
+            let mut stream = Vec::new();
+            write_frame(&mut stream, &RemoteMessage::Incoming(message)).unwrap();
+
+            match read_frame(&mut stream.as_slice()).unwrap() {
+                Some(RemoteMessage::Incoming(message)) => assert_eq!(
+                    serde_json::to_value(&message).unwrap(),
+                    serde_json::from_str::<Value>(payload).unwrap(),
+                ),
+                other => panic!("unexpected frame: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_oversized_frame() {
+        let prefix = (MAX_FRAME_LENGTH + 1).to_be_bytes();
+
+        match read_frame(&mut &prefix[..]) {
+            Err(Error::Io(_)) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_partial_length_prefix() {
+        for len in 1..4 {
+            let prefix = [0; 3];
+
+            match read_frame(&mut &prefix[..len]) {
+                Err(Error::Io(ref why)) => {
+                    assert_eq!(why.kind(), IoErrorKind::UnexpectedEof);
+                },
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        assert!(read_frame(&mut &[][..]).unwrap().is_none());
+    }
+}
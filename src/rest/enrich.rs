@@ -0,0 +1,196 @@
+//! Extension points to change loaded tracks before they're returned by the
+//! owned REST clients.
+//!
+//! Enrichers are registered on a client with its `add_enricher` method, and
+//! run in the order they were added on every track loaded with one of its
+//! `load_tracks` methods. This keeps presentation concerns, such as cleaning
+//! up titles or looking up artwork, in one place.
+//!
+//! # Examples
+//!
+//! Remove formatting characters from the titles of all loaded tracks:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "hyper")]
+//! # fn main() -> lavalink::Result<()> {
+//! use lavalink::rest::hyper::RestClient;
+//! use lavalink::rest::LoadedTrack;
+//! use lavalink::sanitize::SanitizePolicy;
+//!
+//! let mut client = RestClient::new("127.0.0.1:2333", "test_password")?;
+//! client.add_enricher(|track: &mut LoadedTrack| {
+//!     track.info.title = track.info.sanitized_title(&SanitizePolicy::plain());
+//! });
+//! #     Ok(())
+//! # }
+//! #
+//! # #[cfg(not(feature = "hyper"))]
+//! # fn main() {}
+//! ```
+
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::sync::Arc;
+use super::{Load, LoadedTrack};
+
+#[cfg(feature = "hyper")]
+use crate::Error;
+#[cfg(feature = "hyper")]
+use futures::{future, Future};
+
+/// A step that changes a loaded track, such as to fill in missing
+/// information.
+///
+/// This is implemented for closures taking a `&mut LoadedTrack`.
+pub trait Enricher: Send + Sync {
+    /// Changes a loaded track.
+    fn enrich(&self, track: &mut LoadedTrack);
+}
+
+impl<F: Fn(&mut LoadedTrack) + Send + Sync> Enricher for F {
+    fn enrich(&self, track: &mut LoadedTrack) {
+        self(track)
+    }
+}
+
+/// A step that changes a loaded track asynchronously, such as by requesting
+/// its artwork from another service.
+///
+/// This is used by the Hyper client, and is implemented for every
+/// [`Enricher`].
+///
+/// [`Enricher`]: trait.Enricher.html
+#[cfg(feature = "hyper")]
+pub trait AsyncEnricher: Send + Sync {
+    /// Changes a loaded track, resolving to the changed track.
+    ///
+    /// If the future fails, the load fails with its error.
+    fn enrich(
+        &self,
+        track: LoadedTrack,
+    ) -> Box<dyn Future<Item = LoadedTrack, Error = Error> + Send>;
+}
+
+#[cfg(feature = "hyper")]
+impl<T: Enricher> AsyncEnricher for T {
+    fn enrich(
+        &self,
+        mut track: LoadedTrack,
+    ) -> Box<dyn Future<Item = LoadedTrack, Error = Error> + Send> {
+        Enricher::enrich(self, &mut track);
+
+        Box::new(future::ok(track))
+    }
+}
+
+/// The enrichers registered on a client, in the order they were added.
+pub(crate) struct Enrichers<E: ?Sized>(Vec<Arc<E>>);
+
+impl<E: ?Sized> Enrichers<E> {
+    pub(crate) fn push(&mut self, enricher: Arc<E>) {
+        self.0.push(enricher);
+    }
+}
+
+#[cfg(any(feature = "reqwest", test))]
+impl Enrichers<dyn Enricher> {
+    pub(crate) fn apply(&self, load: &mut Load) {
+        for track in &mut load.tracks {
+            for enricher in &self.0 {
+                enricher.enrich(track);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl Enrichers<dyn AsyncEnricher> {
+    pub(crate) fn apply(
+        &self,
+        request: Box<dyn Future<Item = Load, Error = Error> + Send>,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        if self.0.is_empty() {
+            return request;
+        }
+
+        let enrichers = self.0.clone();
+
+        Box::new(request.and_then(move |mut load| {
+            let tracks = load.tracks.drain(..).map(|track| {
+                let start = Box::new(future::ok(track))
+                    as Box<dyn Future<Item = _, Error = _> + Send>;
+
+                enrichers.iter().fold(start, |track, enricher| {
+                    let enricher = Arc::clone(enricher);
+
+                    Box::new(track.and_then(move |t| enricher.enrich(t)))
+                })
+            }).collect::<Vec<_>>();
+
+            future::join_all(tracks).map(move |tracks| {
+                load.tracks = tracks;
+
+                load
+            })
+        }))
+    }
+}
+
+impl<E: ?Sized> Clone for Enrichers<E> {
+    fn clone(&self) -> Self {
+        Enrichers(self.0.clone())
+    }
+}
+
+impl<E: ?Sized> Debug for Enrichers<E> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "Enrichers({})", self.0.len())
+    }
+}
+
+impl<E: ?Sized> Default for Enrichers<E> {
+    fn default() -> Self {
+        Enrichers(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use std::sync::Arc;
+    use super::{Enricher, Enrichers, Load, LoadedTrack};
+
+    #[test]
+    fn test_apply_in_order() {
+        let mut load = serde_json::from_str::<Load>(r#"{
+  "loadType": "TRACK_LOADED",
+  "playlistInfo": {},
+  "tracks": [{
+    "track": "QAAAjQIAJVJpY2sgQXN0bGV5",
+    "info": {
+      "title": "Never Gonna Give You Up",
+      "author": "RickAstleyVEVO",
+      "length": 212000,
+      "identifier": "dQw4w9WgXcQ",
+      "uri": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+      "isStream": false,
+      "isSeekable": true,
+      "position": 0
+    }
+  }]
+}"#).unwrap();
+
+        let mut enrichers = Enrichers::<dyn Enricher>::default();
+        enrichers.push(Arc::new(|track: &mut LoadedTrack| {
+            track.info.title.push_str(" (Official)");
+        }));
+        enrichers.push(Arc::new(|track: &mut LoadedTrack| {
+            track.info.title = track.info.title.to_uppercase();
+        }));
+        enrichers.apply(&mut load);
+
+        assert_eq!(
+            load.tracks[0].info.title,
+            "NEVER GONNA GIVE YOU UP (OFFICIAL)",
+        );
+    }
+}
@@ -1,16 +1,1063 @@
-//! A trait implementation for Hyper's Client for working with the Lavalink
-//! REST API.
+//! A trait implementation for Hyper's Client and an owned client for working
+//! with the Lavalink REST API.
 
-use crate::{Error, Result};
-use futures::{Future, Stream, future};
+use crate::credentials::{CredentialProvider, ProviderSlot};
+use crate::{Error, Result, Secret};
+use futures::future::{self, Either, Loop, Shared};
+use futures::{Async, Future, Poll, Stream};
 use hyper::client::connect::Connect;
-use hyper::header::{CONTENT_TYPE, HeaderValue};
-use hyper::{Body, Client, Method, Request, Uri};
-use percent_encoding::{self, DEFAULT_ENCODE_SET};
+use hyper::client::{Builder as HyperBuilder, HttpConnector, ResponseFuture};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE, HeaderValue};
+use hyper::{Body, Client, HeaderMap, Method, Request, Uri};
 use serde::de::DeserializeOwned;
 use serde_json;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
 use std::str::FromStr;
-use super::{Load, LoadedTrack};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::time::Duration;
+use super::enrich::{AsyncEnricher, Enrichers};
+use super::metrics::{self, RestMetrics};
+use super::{
+    decode_single_track_body,
+    take_single_track,
+    DecodeTrackResponse,
+    DECODE_TRACK_QUERY_LIMIT,
+    DEFAULT_MAX_IDENTIFIER_LENGTH,
+    FallbackLoad,
+    FallbackPlan,
+    Load,
+    LoadedTrack,
+    NodeInfo,
+    NodeVersion,
+    Player,
+    PlayerUpdateRequest,
+    Session,
+    Source,
+};
+
+use super::route_planner::{self, RoutePlannerStatus};
+
+#[cfg(feature = "plugin-lavalyrics")]
+use super::lavalyrics::{self, Lyrics};
+#[cfg(feature = "plugin-lavasearch")]
+use super::lavasearch::{self, SearchResult, SearchType};
+
+/// A Hyper Client wrapper used to communicate with a LavaLink node.
+///
+/// Use [`RestClient::builder`] to configure connection reuse or to use a
+/// custom connector.
+///
+/// [`RestClient::builder`]: #method.builder
+#[derive(Debug)]
+pub struct RestClient<C = HttpConnector> {
+    client: Client<C, Body>,
+    credentials: ProviderSlot,
+    enrichers: Enrichers<dyn AsyncEnricher>,
+    extra_headers: HeaderMap,
+    host: String,
+    in_flight: Arc<InFlightLoads>,
+    max_identifier_length: Option<usize>,
+    metrics: Arc<RestMetrics>,
+    password: RwLock<Secret>,
+    session_id: Option<String>,
+}
+
+impl RestClient<HttpConnector> {
+    /// Creates a new Hyper Client wrapper used to communicate with a LavaLink
+    /// node, using Hyper's default settings.
+    ///
+    /// The host is parsed with [`parse_host`], so it can be given either as
+    /// `host:port` or as a URL.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::hyper::RestClient;
+    ///
+    /// let client = RestClient::new("127.0.0.1:2333", "test_password")?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidHost`] if the host could not be parsed.
+    ///
+    /// [`Error::InvalidHost`]: ../../enum.Error.html#variant.InvalidHost
+    /// [`parse_host`]: ../fn.parse_host.html
+    #[inline]
+    pub fn new(
+        host: impl AsRef<str>,
+        password: impl Into<Vec<u8>>,
+    ) -> Result<Self> {
+        RestClientBuilder::new().build(host, password)
+    }
+
+    /// Creates a builder to configure a new client.
+    pub fn builder() -> RestClientBuilder {
+        RestClientBuilder::new()
+    }
+}
+
+impl<C: Connect + 'static> RestClient<C> {
+    /// Returns the underlying Hyper client.
+    pub fn client(&self) -> &Client<C, Body> {
+        &self.client
+    }
+
+    /// Returns the latency statistics of the requests made by the client.
+    pub fn metrics(&self) -> &Arc<RestMetrics> {
+        &self.metrics
+    }
+
+    /// Returns the ID of the node session used by Lavalink v4 endpoints, if
+    /// one was set.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Sets the ID of the node session used by Lavalink v4 endpoints.
+    ///
+    /// This is the session ID received in the node's [`Ready`] message.
+    ///
+    /// [`Ready`]: ../../model/struct.Ready.html
+    pub fn set_session_id(&mut self, session_id: impl Into<String>) {
+        self.session_id = Some(session_id.into());
+    }
+
+    /// Replaces the password used to authenticate with the node, such as
+    /// after it was rotated.
+    ///
+    /// Requests started after this call use the new password, while requests
+    /// that were already sent keep the old one.
+    pub fn set_password(&self, password: impl Into<Vec<u8>>) {
+        self.replace_password(Secret::new(password));
+    }
+
+    /// Sets the provider that the password used to authenticate with the
+    /// node is read from, and reads it.
+    ///
+    /// See the [`credentials`] module for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns the provider's error if the password couldn't be read, in
+    /// which case the provider and password are left unchanged.
+    ///
+    /// [`credentials`]: ../../credentials/index.html
+    pub fn set_credential_provider(
+        &mut self,
+        provider: impl CredentialProvider + 'static,
+    ) -> Result<()> {
+        self.replace_password(provider.password()?);
+        self.credentials.set(Box::new(provider));
+
+        Ok(())
+    }
+
+    /// Reads the password again from the client's credential provider, such
+    /// as after it was rotated.
+    ///
+    /// This does nothing if no provider was set.
+    ///
+    /// # Errors
+    ///
+    /// Returns the provider's error if the password couldn't be read, in
+    /// which case the current password is kept.
+    pub fn refresh_credentials(&self) -> Result<()> {
+        if let Some(password) = self.credentials.password() {
+            self.replace_password(password?);
+        }
+
+        Ok(())
+    }
+
+    fn replace_password(&self, password: Secret) {
+        *self.password.write().unwrap_or_else(|poisoned| {
+            poisoned.into_inner()
+        }) = password;
+    }
+
+    /// Adds a step that changes every track loaded by the client's
+    /// `load_tracks` methods, run after the enrichers that were already
+    /// added.
+    ///
+    /// Both [`Enricher`]s and [`AsyncEnricher`]s can be added. See the
+    /// [`enrich`] module for an example.
+    ///
+    /// [`AsyncEnricher`]: ../enrich/trait.AsyncEnricher.html
+    /// [`Enricher`]: ../enrich/trait.Enricher.html
+    /// [`enrich`]: ../enrich/index.html
+    pub fn add_enricher(&mut self, enricher: impl AsyncEnricher + 'static) {
+        self.enrichers.push(Arc::new(enricher));
+    }
+
+    /// Sets the longest identifier that the client's `load_tracks` methods
+    /// load, in bytes, or `None` to allow identifiers of any length.
+    ///
+    /// Identifiers are checked before sending a request, so that garbage
+    /// input isn't sent to the node. This defaults to
+    /// [`DEFAULT_MAX_IDENTIFIER_LENGTH`], which can be raised for source
+    /// plugins that accept long `data:` URIs. Identifiers with control
+    /// characters are always rejected.
+    ///
+    /// [`DEFAULT_MAX_IDENTIFIER_LENGTH`]: ../constant.DEFAULT_MAX_IDENTIFIER_LENGTH.html
+    pub fn set_max_identifier_length(&mut self, max: Option<usize>) {
+        self.max_identifier_length = max;
+    }
+
+    /// Sets headers to send with every request in addition to the ones the
+    /// library sets, such as for an authenticating proxy in front of the
+    /// node.
+    ///
+    /// This replaces any extra headers that were set before.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use hyper::header::{HeaderMap, HeaderValue};
+    /// use lavalink::rest::hyper::RestClient;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-Proxy-Token", HeaderValue::from_static("secret"));
+    ///
+    /// let mut client = RestClient::new("127.0.0.1:2333", "test_password")?;
+    /// client.set_extra_headers(headers)?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReservedHeader`] if a header is one that the library
+    /// sets itself, such as `Authorization`.
+    ///
+    /// [`Error::ReservedHeader`]: ../../enum.Error.html#variant.ReservedHeader
+    pub fn set_extra_headers(&mut self, headers: HeaderMap) -> Result<()> {
+        super::check_extra_headers(headers.keys().map(|name| name.as_str()))?;
+        self.extra_headers = headers;
+
+        Ok(())
+    }
+
+    fn check_identifier(&self, identifier: &str) -> Result<()> {
+        super::check_identifier(identifier, self.max_identifier_length)
+    }
+
+    fn password(&self) -> RwLockReadGuard<'_, Secret> {
+        // the password is replaced in a single assignment, so a panic while
+        // holding the lock can't leave it partially written
+        self.password.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Loads tracks matching an identifier via the node.
+    ///
+    /// Concurrent loads of the same identifier share a single request to the
+    /// node, and all receive the same result. This avoids sending many
+    /// identical requests when, for example, several guilds search for the
+    /// same query at once.
+    ///
+    /// # Errors
+    ///
+    /// If other loads shared the request, a failed request resolves to an
+    /// [`Error::Shared`] containing the error of the request.
+    ///
+    /// [`Error::Shared`]: ../../enum.Error.html#variant.Shared
+    pub fn load_tracks(
+        &self,
+        identifier: impl AsRef<str>,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        self._load_tracks(identifier.as_ref())
+    }
+
+    fn _load_tracks(
+        &self,
+        identifier: &str,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        // checked before sharing, so that the error isn't a shared one
+        if let Err(why) = self.check_identifier(identifier) {
+            return Box::new(future::err(why));
+        }
+
+        let mut loads = self.in_flight.lock();
+
+        if let Some(load) = loads.get_mut(identifier) {
+            load.handles += 1;
+            load.shared.store(true, Ordering::SeqCst);
+
+            return Box::new(LoadHandle {
+                future: Some(load.future.clone()),
+                in_flight: Arc::clone(&self.in_flight),
+                key: identifier.to_owned(),
+                shared: Arc::clone(&load.shared),
+            });
+        }
+
+        let shared = Arc::new(AtomicBool::new(false));
+        let in_flight = Arc::clone(&self.in_flight);
+        let key = identifier.to_owned();
+        let flag = Arc::clone(&shared);
+        let request = self.load_tracks_with(identifier, &[])
+            .then(move |result| {
+                in_flight.remove(&key, &flag);
+
+                result.map_err(Arc::new)
+            });
+        let future = (Box::new(request) as SharedLoadFuture).shared();
+
+        loads.insert(identifier.to_owned(), InFlightLoad {
+            future: future.clone(),
+            handles: 1,
+            shared: Arc::clone(&shared),
+        });
+
+        Box::new(LoadHandle {
+            future: Some(future),
+            in_flight: Arc::clone(&self.in_flight),
+            key: identifier.to_owned(),
+            shared,
+        })
+    }
+
+    /// Loads tracks matching an identifier via the node, appending additional
+    /// query parameters to the request.
+    ///
+    /// This is useful for source plugins that accept extra parameters, such
+    /// as region hints. The keys and values are percent-encoded.
+    pub fn load_tracks_with(
+        &self,
+        identifier: impl AsRef<str>,
+        params: &[(&str, &str)],
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        if let Err(why) = self.check_identifier(identifier.as_ref()) {
+            return Box::new(future::err(why));
+        }
+
+        let request = self.timed("loadtracks", |requester| requester.load_tracks_with(
+            &self.host,
+            &*self.password(),
+            identifier,
+            params,
+        ));
+
+        self.enrichers.apply(request)
+    }
+
+    /// Loads tracks matching an identifier via the node, reporting the
+    /// progress of receiving the response.
+    ///
+    /// The callback is called each time a part of the response is received,
+    /// with the number of tracks received so far in
+    /// [`LoadProgress::tracks_received`], and a final time with
+    /// [`LoadProgress::tracks_parsed`] set once the tracks have been parsed.
+    /// This is useful to show feedback while loading very large playlists.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::hyper::RestClient;
+    ///
+    /// let client = RestClient::new("127.0.0.1:2333", "test_password")?;
+    /// let load = client.load_tracks_with_progress(
+    ///     "https://www.youtube.com/playlist?list=PL0123",
+    ///     |progress| match progress.tracks_parsed {
+    ///         Some(tracks) => println!("Loaded {} tracks", tracks),
+    ///         None => println!("Received {} tracks", progress.tracks_received),
+    ///     },
+    /// );
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`LoadProgress::tracks_parsed`]: struct.LoadProgress.html#structfield.tracks_parsed
+    /// [`LoadProgress::tracks_received`]: struct.LoadProgress.html#structfield.tracks_received
+    pub fn load_tracks_with_progress(
+        &self,
+        identifier: impl AsRef<str>,
+        progress: impl FnMut(LoadProgress) + Send + 'static,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        if let Err(why) = self.check_identifier(identifier.as_ref()) {
+            return Box::new(future::err(why));
+        }
+
+        let request = self.timed("loadtracks", |requester| requester.load_tracks_with_progress(
+            &self.host,
+            &*self.password(),
+            identifier,
+            progress,
+        ));
+
+        self.enrichers.apply(request)
+    }
+
+    /// Loads tracks matching an identifier via the node, searching other
+    /// sources for the track if none were found.
+    ///
+    /// If the identifier is an encoded track, its URL is loaded, and its
+    /// author and title are searched for on each source in order until a
+    /// load finds tracks. If the identifier is a search, its query is
+    /// searched for instead. Other identifiers, such as URLs, have nothing to
+    /// search for, so they aren't retried.
+    ///
+    /// The first load is shared with concurrent loads of the same
+    /// identifier, like [`load_tracks`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use futures::Future;
+    /// use lavalink::rest::hyper::RestClient;
+    /// use lavalink::rest::Source;
+    ///
+    /// let client = RestClient::new("127.0.0.1:2333", "test_password")?;
+    /// let load = client.load_with_fallback("ytsearch:never gonna", vec![
+    ///     Source::YouTubeMusic,
+    ///     Source::SoundCloud,
+    /// ]).map(|result| match result.source {
+    ///     Some(source) => println!("Found on {:?}", source),
+    ///     None => println!("Found on the original source"),
+    /// });
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`load_tracks`]: #method.load_tracks
+    pub fn load_with_fallback(
+        &self,
+        identifier: impl AsRef<str>,
+        sources: impl IntoIterator<Item = Source>,
+    ) -> Box<dyn Future<Item = FallbackLoad, Error = Error> + Send>
+        where C: Clone {
+        let plan = FallbackPlan::new(identifier.as_ref());
+        let first = self._load_tracks(&plan.primary).map(|load| FallbackLoad {
+            load,
+            source: None,
+        });
+        let fallbacks = plan.fallbacks(sources);
+
+        if fallbacks.is_empty() {
+            return Box::new(first);
+        }
+
+        // the fallbacks are loaded after this returns, so they need their own
+        // copies of the client's settings
+        let client = self.client.clone();
+        let enrichers = self.enrichers.clone();
+        let headers = self.extra_headers.clone();
+        let host = self.host.clone();
+        let metrics = Arc::clone(&self.metrics);
+        let password = self.password().clone();
+
+        Box::new(first.and_then(move |first| {
+            future::loop_fn(
+                (first, fallbacks.into_iter()),
+                move |(result, mut fallbacks)| {
+                    let next = if result.load.found_tracks() {
+                        None
+                    } else {
+                        fallbacks.next()
+                    };
+                    let (source, identifier) = match next {
+                        Some(fallback) => fallback,
+                        None => {
+                            return Either::A(future::ok(Loop::Break(result)));
+                        },
+                    };
+
+                    let request_id = metrics::next_request_id();
+                    let requester = WithHeaders {
+                        client: &client,
+                        headers: &headers,
+                        request_id,
+                    };
+                    let request = timed(
+                        &metrics,
+                        "loadtracks",
+                        request_id,
+                        requester.load_tracks(&host, &password, identifier),
+                    );
+
+                    Either::B(enrichers.apply(request).map(move |load| {
+                        Loop::Continue((FallbackLoad {
+                            load,
+                            source: Some(source),
+                        }, fallbacks))
+                    }))
+                },
+            )
+        }))
+    }
+
+    /// Decodes a track via the node.
+    pub fn decode_track(
+        &self,
+        track: impl Into<String>,
+    ) -> Box<dyn Future<Item = LoadedTrack, Error = Error> + Send> {
+        self.timed(
+            "decodetrack",
+            |requester| requester.decode_track(&self.host, &*self.password(), track),
+        )
+    }
+
+    /// Decodes a vector of tracks via the node.
+    pub fn decode_tracks(
+        &self,
+        tracks: impl IntoIterator<Item = impl Into<Vec<u8>>>,
+    ) -> Box<dyn Future<Item = Vec<LoadedTrack>, Error = Error> + Send> {
+        self.timed(
+            "decodetracks",
+            |requester| requester.decode_tracks(&self.host, &*self.password(), tracks),
+        )
+    }
+
+    /// Retrieves the version of the node.
+    pub fn version(
+        &self,
+    ) -> Box<dyn Future<Item = NodeVersion, Error = Error> + Send> {
+        self.timed("version", |requester| requester.version(&self.host, &*self.password()))
+    }
+
+    /// Retrieves information about a Lavalink v4 node, such as its version,
+    /// plugins and enabled filters.
+    pub fn info(&self) -> Box<dyn Future<Item = NodeInfo, Error = Error> + Send> {
+        self.timed("info", |requester| requester.info(&self.host, &*self.password()))
+    }
+
+    /// Retrieves the status of the node's route planner.
+    ///
+    /// The future resolves to `None` if the node has no route planner
+    /// configured.
+    pub fn route_planner_status(
+        &self,
+    ) -> Box<dyn Future<Item = Option<RoutePlannerStatus>, Error = Error> + Send> {
+        self.timed(
+            "route_planner_status",
+            |requester| requester.route_planner_status(&self.host, &*self.password()),
+        )
+    }
+
+    /// Configures whether the node session can be resumed, and for how long
+    /// after the connection to the node closes.
+    ///
+    /// The future resolves to [`Error::MissingSessionId`] if no session ID
+    /// was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    pub fn update_session(
+        &self,
+        resuming: bool,
+        timeout: Duration,
+    ) -> Box<dyn Future<Item = Session, Error = Error> + Send> {
+        let session_id = match self.session_id() {
+            Some(session_id) => session_id,
+            None => return Box::new(future::err(Error::MissingSessionId)),
+        };
+
+        self.timed("sessions", |requester| requester.update_session(
+            &self.host,
+            &*self.password(),
+            session_id,
+            resuming,
+            timeout,
+        ))
+    }
+
+    /// Applies a set of changes to a guild's player on a Lavalink v4 node in
+    /// a single request, resolving to the updated player.
+    ///
+    /// This uses the session ID set with [`set_session_id`]. The future
+    /// resolves to [`Error::MissingSessionId`] if none was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    /// [`set_session_id`]: #method.set_session_id
+    pub fn update_player(
+        &self,
+        guild_id: impl AsRef<str>,
+        update: &PlayerUpdateRequest,
+    ) -> Box<dyn Future<Item = Player, Error = Error> + Send> {
+        let session_id = match self.session_id() {
+            Some(session_id) => session_id,
+            None => return Box::new(future::err(Error::MissingSessionId)),
+        };
+
+        self.timed("players", |requester| requester.update_player(
+            &self.host,
+            &*self.password(),
+            session_id,
+            guild_id,
+            update,
+        ))
+    }
+
+    /// Searches for a query via the node's LavaSearch plugin, returning
+    /// results of the given types.
+    #[cfg(feature = "plugin-lavasearch")]
+    pub fn search_advanced(
+        &self,
+        query: impl AsRef<str>,
+        types: impl IntoIterator<Item = SearchType>,
+    ) -> Box<dyn Future<Item = SearchResult, Error = Error> + Send> {
+        self.timed(
+            "loadsearch",
+            |requester| requester.search_advanced(&self.host, &*self.password(), query, types),
+        )
+    }
+
+    /// Retrieves the lyrics of an encoded track via the node's LavaLyrics
+    /// plugin, resolving to `None` if none were found.
+    ///
+    /// If `skip_track_source` is `true`, lyrics aren't retrieved from the
+    /// track's own source, such as YouTube's captions.
+    #[cfg(feature = "plugin-lavalyrics")]
+    pub fn lyrics(
+        &self,
+        track: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send> {
+        self.timed("lyrics", |requester| requester.lyrics(
+            &self.host,
+            &*self.password(),
+            track,
+            skip_track_source,
+        ))
+    }
+
+    /// Retrieves the lyrics of a guild player's current track via the node's
+    /// LavaLyrics plugin, resolving to `None` if none were found.
+    ///
+    /// This uses the session ID set with [`set_session_id`]. The future
+    /// resolves to [`Error::MissingSessionId`] if none was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    /// [`set_session_id`]: #method.set_session_id
+    #[cfg(feature = "plugin-lavalyrics")]
+    pub fn player_lyrics(
+        &self,
+        guild_id: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send> {
+        let session_id = match self.session_id() {
+            Some(session_id) => session_id,
+            None => return Box::new(future::err(Error::MissingSessionId)),
+        };
+
+        self.timed("lyrics", |requester| requester.player_lyrics(
+            &self.host,
+            &*self.password(),
+            session_id,
+            guild_id,
+            skip_track_source,
+        ))
+    }
+
+    /// Subscribes or unsubscribes a guild's player to live lyrics events via
+    /// the node's LavaLyrics plugin.
+    ///
+    /// This uses the session ID set with [`set_session_id`]. The future
+    /// resolves to [`Error::MissingSessionId`] if none was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    /// [`set_session_id`]: #method.set_session_id
+    #[cfg(feature = "plugin-lavalyrics")]
+    pub fn set_lyrics_subscription(
+        &self,
+        guild_id: impl AsRef<str>,
+        subscribed: bool,
+    ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+        let session_id = match self.session_id() {
+            Some(session_id) => session_id,
+            None => return Box::new(future::err(Error::MissingSessionId)),
+        };
+
+        self.timed("lyrics", |requester| requester.set_lyrics_subscription(
+            &self.host,
+            &*self.password(),
+            session_id,
+            guild_id,
+            subscribed,
+        ))
+    }
+
+    /// Builds a request with the client's extra headers and a new request ID,
+    /// and records its latency and result in the client's metrics.
+    fn timed<T, F>(
+        &self,
+        endpoint: &'static str,
+        request: F,
+    ) -> Box<dyn Future<Item = T, Error = Error> + Send>
+        where T: Send + 'static,
+              F: FnOnce(&WithHeaders<'_, C>) -> Box<dyn Future<Item = T, Error = Error> + Send> {
+        let request_id = metrics::next_request_id();
+        let requester = WithHeaders {
+            client: &self.client,
+            headers: &self.extra_headers,
+            request_id,
+        };
+
+        timed(&self.metrics, endpoint, request_id, request(&requester))
+    }
+}
+
+/// Records the latency and result of a request in a client's metrics.
+///
+/// Hyper doesn't send a request until its future is first polled, so the
+/// timer is only started then.
+fn timed<T: Send + 'static>(
+    metrics: &Arc<RestMetrics>,
+    endpoint: &'static str,
+    request_id: usize,
+    request: Box<dyn Future<Item = T, Error = Error> + Send>,
+) -> Box<dyn Future<Item = T, Error = Error> + Send> {
+    let metrics = Arc::clone(metrics);
+
+    Box::new(future::lazy(move || {
+        let timer = metrics.start(endpoint, request_id);
+
+        request.then(move |result| {
+            metrics.finish(timer, result.is_ok());
+
+            result
+        })
+    }))
+}
+
+type SharedLoadFuture = Box<dyn Future<Item = Load, Error = Arc<Error>> + Send>;
+
+/// A track load that is waiting on a response.
+struct InFlightLoad {
+    future: Shared<SharedLoadFuture>,
+    /// The number of loads waiting on the response.
+    handles: usize,
+    /// Whether more than one load waited on the response, in which case its
+    /// error is shared.
+    shared: Arc<AtomicBool>,
+}
+
+/// The track loads that are currently waiting on a response, keyed by their
+/// identifier.
+#[derive(Default)]
+struct InFlightLoads(Mutex<HashMap<String, InFlightLoad>>);
+
+impl InFlightLoads {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, InFlightLoad>> {
+        // the map is only modified with single insertions, removals and
+        // counter changes, so a panic while holding the lock can't leave it
+        // inconsistent
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Removes a load, unless it was already replaced by a newer load of
+    /// the same identifier.
+    fn remove(&self, key: &str, shared: &Arc<AtomicBool>) {
+        let mut loads = self.lock();
+
+        if loads.get(key).map_or(false, |load| Arc::ptr_eq(&load.shared, shared)) {
+            loads.remove(key);
+        }
+    }
+
+    /// Releases a handle on a load, removing the load if no other handle
+    /// waits on it.
+    fn release(&self, key: &str, shared: &Arc<AtomicBool>) {
+        let mut loads = self.lock();
+
+        let unused = match loads.get_mut(key) {
+            Some(load) if Arc::ptr_eq(&load.shared, shared) => {
+                load.handles -= 1;
+
+                load.handles == 0
+            },
+            _ => false,
+        };
+
+        // dropping the load cancels its request
+        if unused {
+            loads.remove(key);
+        }
+    }
+}
+
+impl Debug for InFlightLoads {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_set().entries(self.lock().keys()).finish()
+    }
+}
+
+/// A load waiting on the response to an in-flight load, which releases the
+/// in-flight load when dropped.
+struct LoadHandle {
+    future: Option<Shared<SharedLoadFuture>>,
+    in_flight: Arc<InFlightLoads>,
+    key: String,
+    shared: Arc<AtomicBool>,
+}
+
+impl Future for LoadHandle {
+    type Item = Load;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Load, Error> {
+        let future = self.future.as_mut().expect("polled after completion");
+
+        let why = match future.poll() {
+            Ok(Async::Ready(load)) => return Ok(Async::Ready((*load).clone())),
+            Ok(Async::NotReady) => return Ok(Async::NotReady),
+            Err(why) => Arc::clone(&*why),
+        };
+
+        if self.shared.load(Ordering::SeqCst) {
+            return Err(Error::Shared(why));
+        }
+
+        // no other load waited on the response, so once this handle's copy
+        // of the result is dropped, the error is this load's alone
+        self.future = None;
+
+        Err(Arc::try_unwrap(why).unwrap_or_else(Error::Shared))
+    }
+}
+
+impl Drop for LoadHandle {
+    fn drop(&mut self) {
+        self.in_flight.release(&self.key, &self.shared);
+    }
+}
+
+/// A builder for a [`RestClient`], configuring the connection pool and
+/// protocol of the underlying Hyper client.
+///
+/// # Examples
+///
+/// Keep idle connections for 30 seconds and only use HTTP/2:
+///
+/// ```rust,no_run
+/// # fn main() -> lavalink::Result<()> {
+/// use lavalink::rest::hyper::RestClient;
+/// use std::time::Duration;
+///
+/// let client = RestClient::builder()
+///     .pool_idle_timeout(Duration::from_secs(30))
+///     .http2_only(true)
+///     .build("http://127.0.0.1:2333", "test_password")?;
+/// #     Ok(())
+/// # }
+/// ```
+///
+/// [`RestClient`]: struct.RestClient.html
+#[derive(Debug, Default)]
+pub struct RestClientBuilder {
+    builder: HyperBuilder,
+}
+
+impl RestClientBuilder {
+    /// Creates a new builder with Hyper's default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long an idle connection is kept in the pool for reuse.
+    ///
+    /// Pass `None` to keep idle connections indefinitely.
+    pub fn pool_idle_timeout(
+        mut self,
+        timeout: impl Into<Option<Duration>>,
+    ) -> Self {
+        self.builder.keep_alive_timeout(timeout);
+
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept in the pool.
+    ///
+    /// Setting this to `0` disables connection reuse.
+    pub fn pool_max_idle(mut self, max: usize) -> Self {
+        self.builder.max_idle_per_host(max);
+
+        self
+    }
+
+    /// Sets whether to only use HTTP/2 to connect to the node.
+    ///
+    /// Defaults to `false`.
+    pub fn http2_only(mut self, enabled: bool) -> Self {
+        self.builder.http2_only(enabled);
+
+        self
+    }
+
+    /// Builds a client connecting over TCP with Hyper's `HttpConnector`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidHost`] if the host could not be parsed with
+    /// [`parse_host`].
+    ///
+    /// [`Error::InvalidHost`]: ../../enum.Error.html#variant.InvalidHost
+    /// [`parse_host`]: ../fn.parse_host.html
+    #[inline]
+    pub fn build(
+        self,
+        host: impl AsRef<str>,
+        password: impl Into<Vec<u8>>,
+    ) -> Result<RestClient<HttpConnector>> {
+        let client = self.builder.build_http();
+
+        Ok(RestClient {
+            client,
+            credentials: ProviderSlot::default(),
+            enrichers: Enrichers::default(),
+            extra_headers: HeaderMap::new(),
+            host: super::parse_host(host)?,
+            in_flight: Arc::default(),
+            max_identifier_length: Some(DEFAULT_MAX_IDENTIFIER_LENGTH),
+            metrics: Arc::new(RestMetrics::default()),
+            password: RwLock::new(Secret::new(password)),
+            session_id: None,
+        })
+    }
+
+    /// Builds a client connecting with the given connector.
+    ///
+    /// This can be used to connect over a Unix socket, or to resolve the host
+    /// with a custom DNS resolver.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidHost`] if the host could not be parsed with
+    /// [`parse_host`].
+    ///
+    /// [`Error::InvalidHost`]: ../../enum.Error.html#variant.InvalidHost
+    /// [`parse_host`]: ../fn.parse_host.html
+    #[inline]
+    pub fn build_with_connector<C: Connect + 'static>(
+        self,
+        connector: C,
+        host: impl AsRef<str>,
+        password: impl Into<Vec<u8>>,
+    ) -> Result<RestClient<C>> {
+        let client = self.builder.build(connector);
+
+        Ok(RestClient {
+            client,
+            credentials: ProviderSlot::default(),
+            enrichers: Enrichers::default(),
+            extra_headers: HeaderMap::new(),
+            host: super::parse_host(host)?,
+            in_flight: Arc::default(),
+            max_identifier_length: Some(DEFAULT_MAX_IDENTIFIER_LENGTH),
+            metrics: Arc::new(RestMetrics::default()),
+            password: RwLock::new(Secret::new(password)),
+            session_id: None,
+        })
+    }
+}
+
+/// The progress of loading tracks with
+/// [`RestClient::load_tracks_with_progress`].
+///
+/// [`RestClient::load_tracks_with_progress`]: struct.RestClient.html#method.load_tracks_with_progress
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LoadProgress {
+    /// The number of bytes of the response received so far.
+    pub bytes_received: u64,
+    /// The length of the response in bytes, if the node sent it.
+    pub content_length: Option<u64>,
+    /// The number of tracks that were loaded.
+    ///
+    /// This is only set in the final progress report, once the whole
+    /// response has been received and parsed.
+    pub tracks_parsed: Option<usize>,
+    /// The number of tracks in the response that have been received
+    /// completely so far.
+    pub tracks_received: usize,
+}
+
+/// Counts the tracks of a load response while it's received, by scanning
+/// for the end of each object in its top-level `tracks` array.
+#[derive(Debug, Default)]
+struct TrackCounter {
+    /// Whether the top-level object expects a key next.
+    expecting_key: bool,
+    /// The last key of the top-level object.
+    key: Vec<u8>,
+    in_key: bool,
+    in_string: bool,
+    /// Whether the previous byte of a string was an unescaped backslash.
+    escaped: bool,
+    /// The arrays and objects that are open.
+    scopes: Vec<Scope>,
+    tracks: usize,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Scope {
+    Array,
+    Object,
+    Tracks,
+}
+
+impl TrackCounter {
+    /// Scans the next part of the response, returning the number of tracks
+    /// received so far.
+    fn feed(&mut self, bytes: &[u8]) -> usize {
+        for &byte in bytes {
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                    self.in_key = false;
+                    continue;
+                }
+
+                if self.in_key {
+                    self.key.push(byte);
+                }
+
+                continue;
+            }
+
+            match byte {
+                b'"' => {
+                    self.in_string = true;
+
+                    if self.scopes.len() == 1 && self.expecting_key {
+                        self.expecting_key = false;
+                        self.in_key = true;
+                        self.key.clear();
+                    }
+                },
+                b'{' => {
+                    self.scopes.push(Scope::Object);
+                    self.expecting_key = self.scopes.len() == 1;
+                },
+                b'[' if self.scopes.len() == 1 && self.key == b"tracks" => {
+                    self.scopes.push(Scope::Tracks);
+                },
+                b'[' => self.scopes.push(Scope::Array),
+                b'}' | b']' => {
+                    self.scopes.pop();
+
+                    if byte == b'}' && self.scopes.last() == Some(&Scope::Tracks) {
+                        self.tracks += 1;
+                    }
+                },
+                b',' if self.scopes.len() == 1 => self.expecting_key = true,
+                _ => {},
+            }
+        }
+
+        self.tracks
+    }
+}
 
 /// Trait to implement for working with the Lavalink REST API over a Hyper
 /// client.
@@ -21,75 +1068,412 @@ pub trait LavalinkRestRequester {
         host: impl AsRef<str>,
         password: impl AsRef<[u8]>,
         identifier: impl AsRef<str>,
-    ) -> Box<Future<Item = Load, Error = Error> + Send>;
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send>;
+
+    /// Loads tracks matching an identifier via a given node, appending
+    /// additional query parameters to the request.
+    fn load_tracks_with(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+        params: &[(&str, &str)],
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send>;
+
+    /// Loads tracks matching an identifier via a given node, reporting the
+    /// progress of receiving the response.
+    fn load_tracks_with_progress(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+        progress: impl FnMut(LoadProgress) + Send + 'static,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send>;
+
+    /// Decodes a track via a given node.
+    ///
+    /// Very long tracks are sent in the body of a `/decodetracks` request
+    /// rather than in the URI.
+    fn decode_track(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        track: impl Into<String>,
+    ) -> Box<dyn Future<Item = LoadedTrack, Error = Error> + Send>;
+
+    /// Decodes a vector of tracks via a given node.
+    fn decode_tracks(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        tracks: impl IntoIterator<Item = impl Into<Vec<u8>>>,
+    ) -> Box<dyn Future<Item = Vec<LoadedTrack>, Error = Error> + Send>;
+
+    /// Retrieves the version of a given node.
+    fn version(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Box<dyn Future<Item = NodeVersion, Error = Error> + Send>;
+
+    /// Retrieves information about a given Lavalink v4 node.
+    fn info(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Box<dyn Future<Item = NodeInfo, Error = Error> + Send>;
+
+    /// Retrieves the status of a given node's route planner, resolving to
+    /// `None` if it has none.
+    fn route_planner_status(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Box<dyn Future<Item = Option<RoutePlannerStatus>, Error = Error> + Send>;
+
+    /// Configures the resuming of a session on a given Lavalink v4 node.
+    fn update_session(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        resuming: bool,
+        timeout: Duration,
+    ) -> Box<dyn Future<Item = Session, Error = Error> + Send>;
+
+    /// Applies a set of changes to a guild's player on a given Lavalink v4
+    /// node.
+    fn update_player(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        update: &PlayerUpdateRequest,
+    ) -> Box<dyn Future<Item = Player, Error = Error> + Send>;
+
+    /// Searches for a query via a given node's LavaSearch plugin.
+    #[cfg(feature = "plugin-lavasearch")]
+    fn search_advanced(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        query: impl AsRef<str>,
+        types: impl IntoIterator<Item = SearchType>,
+    ) -> Box<dyn Future<Item = SearchResult, Error = Error> + Send>;
+
+    /// Retrieves the lyrics of an encoded track via a given node's
+    /// LavaLyrics plugin, resolving to `None` if none were found.
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        track: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send>;
+
+    /// Retrieves the lyrics of a guild player's current track via a given
+    /// node's LavaLyrics plugin, resolving to `None` if none were found.
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn player_lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send>;
+
+    /// Subscribes or unsubscribes a guild's player to live lyrics events via
+    /// a given node's LavaLyrics plugin.
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn set_lyrics_subscription(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        subscribed: bool,
+    ) -> Box<dyn Future<Item = (), Error = Error> + Send>;
+}
+
+mod private {
+    use hyper::client::ResponseFuture;
+    use hyper::{Body, Request};
+
+    /// A client that requests are sent to nodes with.
+    ///
+    /// This is implemented for Hyper's `Client`, and can't be implemented
+    /// outside of the library.
+    pub trait SendRequest {
+        /// Sends a request, resolving to the response.
+        fn send_request(&self, request: Request<Body>) -> ResponseFuture;
+    }
+}
+
+use self::private::SendRequest;
+
+impl<C: Connect + 'static> SendRequest for Client<C, Body> {
+    fn send_request(&self, request: Request<Body>) -> ResponseFuture {
+        self.request(request)
+    }
+}
+
+/// A client that adds extra headers and the ID of the request to every
+/// request.
+struct WithHeaders<'a, C> {
+    client: &'a Client<C, Body>,
+    headers: &'a HeaderMap,
+    request_id: usize,
+}
+
+impl<'a, C: Connect + 'static> SendRequest for WithHeaders<'a, C> {
+    fn send_request(&self, mut request: Request<Body>) -> ResponseFuture {
+        for (name, value) in self.headers {
+            request.headers_mut().append(name, value.clone());
+        }
+
+        request.headers_mut().insert(
+            metrics::REQUEST_ID_HEADER,
+            HeaderValue::from(self.request_id),
+        );
+
+        self.client.request(request)
+    }
+}
+
+impl<S: SendRequest> LavalinkRestRequester for S {
+    fn load_tracks(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        load_tracks(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            identifier.as_ref(),
+            &[],
+        )
+    }
+
+    fn load_tracks_with(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+        params: &[(&str, &str)],
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        load_tracks(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            identifier.as_ref(),
+            params,
+        )
+    }
+
+    fn load_tracks_with_progress(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+        progress: impl FnMut(LoadProgress) + Send + 'static,
+    ) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+        load_tracks_with_progress(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            identifier.as_ref(),
+            progress,
+        )
+    }
 
-    /// Decodes a track via a given node.
     fn decode_track(
         &self,
         host: impl AsRef<str>,
         password: impl AsRef<[u8]>,
         track: impl Into<String>,
-    ) -> Box<Future<Item = LoadedTrack, Error = Error> + Send>;
+    ) -> Box<dyn Future<Item = LoadedTrack, Error = Error> + Send> {
+        decode_track(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            track.into(),
+        )
+    }
 
-    /// Decodes a vector of tracks via a given node.
     fn decode_tracks(
         &self,
         host: impl AsRef<str>,
         password: impl AsRef<[u8]>,
         tracks: impl IntoIterator<Item = impl Into<Vec<u8>>>,
-    ) -> Box<Future<Item = Vec<LoadedTrack>, Error = Error> + Send>;
-}
+    ) -> Box<dyn Future<Item = Vec<LoadedTrack>, Error = Error> + Send> {
+        decode_tracks(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            &tracks.into_iter().map(Into::into).collect::<Vec<_>>(),
+        )
+    }
 
-impl<C: Connect + 'static> LavalinkRestRequester for Client<C, Body> {
-    fn load_tracks(
+    fn version(
         &self,
         host: impl AsRef<str>,
         password: impl AsRef<[u8]>,
-        identifier: impl AsRef<str>,
-    ) -> Box<Future<Item = Load, Error = Error> + Send> {
-        load_tracks(
-            &self,
+    ) -> Box<dyn Future<Item = NodeVersion, Error = Error> + Send> {
+        version(self, host.as_ref(), password.as_ref())
+    }
+
+    fn info(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Box<dyn Future<Item = NodeInfo, Error = Error> + Send> {
+        info(self, host.as_ref(), password.as_ref())
+    }
+
+    fn route_planner_status(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Box<dyn Future<Item = Option<RoutePlannerStatus>, Error = Error> + Send> {
+        route_planner_status(self, host.as_ref(), password.as_ref())
+    }
+
+    fn update_session(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        resuming: bool,
+        timeout: Duration,
+    ) -> Box<dyn Future<Item = Session, Error = Error> + Send> {
+        update_session(
+            self,
             host.as_ref(),
             password.as_ref(),
-            identifier.as_ref(),
+            session_id.as_ref(),
+            &Session::new(resuming, timeout),
         )
     }
 
-    fn decode_track(
+    fn update_player(
         &self,
         host: impl AsRef<str>,
         password: impl AsRef<[u8]>,
-        track: impl Into<String>,
-    ) -> Box<Future<Item = LoadedTrack, Error = Error> + Send> {
-        decode_track(
-            &self,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        update: &PlayerUpdateRequest,
+    ) -> Box<dyn Future<Item = Player, Error = Error> + Send> {
+        update_player(
+            self,
             host.as_ref(),
             password.as_ref(),
-            track.into(),
+            session_id.as_ref(),
+            guild_id.as_ref(),
+            update,
         )
     }
 
-    fn decode_tracks(
+    #[cfg(feature = "plugin-lavasearch")]
+    fn search_advanced(
         &self,
         host: impl AsRef<str>,
         password: impl AsRef<[u8]>,
-        tracks: impl IntoIterator<Item = impl Into<Vec<u8>>>,
-    ) -> Box<Future<Item = Vec<LoadedTrack>, Error = Error> + Send> {
-        decode_tracks(
+        query: impl AsRef<str>,
+        types: impl IntoIterator<Item = SearchType>,
+    ) -> Box<dyn Future<Item = SearchResult, Error = Error> + Send> {
+        search_advanced(
             self,
             host.as_ref(),
             password.as_ref(),
-            &tracks.into_iter().map(Into::into).collect::<Vec<_>>(),
+            query.as_ref(),
+            &types.into_iter().collect::<Vec<_>>(),
+        )
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        track: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send> {
+        let uri = lavalyrics::lyrics_uri(track.as_ref(), skip_track_source);
+
+        lyrics(self, host.as_ref(), password.as_ref(), &uri)
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn player_lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send> {
+        let uri = lavalyrics::player_lyrics_uri(
+            session_id.as_ref(),
+            guild_id.as_ref(),
+            skip_track_source,
+        );
+
+        lyrics(self, host.as_ref(), password.as_ref(), &uri)
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn set_lyrics_subscription(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        subscribed: bool,
+    ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+        set_lyrics_subscription(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            session_id.as_ref(),
+            guild_id.as_ref(),
+            subscribed,
         )
     }
 }
 
-fn decode_track<C: Connect + 'static>(
-    client: &Client<C, Body>,
+fn decode_track<S: SendRequest>(
+    client: &S,
     host: &str,
     password: &[u8],
     track: String,
-) -> Box<Future<Item = LoadedTrack, Error = Error> + Send> {
+) -> Box<dyn Future<Item = LoadedTrack, Error = Error> + Send> {
+    if track.len() > DECODE_TRACK_QUERY_LIMIT {
+        let body = match decode_single_track_body(&track) {
+            Ok(body) => body,
+            Err(why) => return Box::new(future::err(why)),
+        };
+        let body = (body, HeaderValue::from_static("application/json"));
+
+        let request = create_request(
+            Method::POST,
+            "/decodetracks",
+            Some(body),
+            host,
+            password,
+        );
+        let request = match request {
+            Ok(v) => v,
+            Err(why) => return Box::new(future::err(why)),
+        };
+
+        return Box::new(run_request(client, request).and_then(take_single_track));
+    }
+
     let uri = format!("/decodetrack?track={}", track);
     let request = create_request(
         Method::GET,
@@ -104,21 +1488,18 @@ fn decode_track<C: Connect + 'static>(
     };
 
     Box::new(run_request(client, request)
-        .map(|info| {
-            LoadedTrack {
-                info,
-                track,
-            }
+        .map(|response: DecodeTrackResponse| {
+            response.into_loaded_track(track)
         })
         .from_err())
 }
 
-fn decode_tracks<C: Connect + 'static>(
-    client: &Client<C, Body>,
+fn decode_tracks<S: SendRequest>(
+    client: &S,
     host: &str,
     password: &[u8],
     tracks: &[Vec<u8>],
-) -> Box<Future<Item = Vec<LoadedTrack>, Error = Error> + Send> {
+) -> Box<dyn Future<Item = Vec<LoadedTrack>, Error = Error> + Send> {
     let tracks = match serde_json::to_vec(&tracks) {
         Ok(tracks) => tracks,
         Err(why) => return Box::new(future::err(Error::Json(why))),
@@ -140,19 +1521,39 @@ fn decode_tracks<C: Connect + 'static>(
     run_request(client, request)
 }
 
-fn load_tracks<C: Connect + 'static>(
-    client: &Client<C, Body>,
+fn load_tracks<S: SendRequest>(
+    client: &S,
     host: &str,
     password: &[u8],
     identifier: &str,
-) -> Box<Future<Item = Load, Error = Error> + Send> {
-    // url encoding the identifier
-    let identifier = percent_encoding::utf8_percent_encode(
-        identifier,
-        DEFAULT_ENCODE_SET,
+    params: &[(&str, &str)],
+) -> Box<dyn Future<Item = Load, Error = Error> + Send> {
+    let uri = super::load_tracks_uri(identifier, params);
+    let request = create_request(
+        Method::GET,
+        uri.as_ref(),
+        None,
+        host,
+        password,
     );
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    run_request(client, request)
+}
 
-    let uri = format!("/loadtracks?identifier={}", identifier);
+fn load_tracks_with_progress<S, F>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+    identifier: &str,
+    progress: F,
+) -> Box<dyn Future<Item = Load, Error = Error> + Send>
+    where S: SendRequest,
+          F: FnMut(LoadProgress) + Send + 'static {
+    let uri = super::load_tracks_uri(identifier, &[]);
     let request = create_request(
         Method::GET,
         uri.as_ref(),
@@ -165,9 +1566,230 @@ fn load_tracks<C: Connect + 'static>(
         Err(why) => return Box::new(future::err(why)),
     };
 
+    Box::new(client.send_request(request)
+        .from_err::<Error>()
+        .and_then(move |res| {
+            let status = res.status().as_u16();
+            let content_length = res
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok());
+
+            let state = (Vec::new(), TrackCounter::default(), progress);
+
+            res.into_body()
+                .from_err::<Error>()
+                .fold(state, move |(mut body, mut counter, mut progress), chunk| {
+                    body.extend_from_slice(&chunk);
+                    progress(LoadProgress {
+                        bytes_received: body.len() as u64,
+                        content_length,
+                        tracks_parsed: None,
+                        tracks_received: counter.feed(&chunk),
+                    });
+
+                    Ok::<_, Error>((body, counter, progress))
+                })
+                .and_then(move |(body, _, mut progress)| {
+                    debug!("Status: {}, body: {}", status, String::from_utf8_lossy(&body));
+
+                    let body = super::check_status(status, body)?;
+                    let load = serde_json::from_slice::<Load>(&body)?;
+                    progress(LoadProgress {
+                        bytes_received: body.len() as u64,
+                        content_length,
+                        tracks_parsed: Some(load.tracks.len()),
+                        tracks_received: load.tracks.len(),
+                    });
+
+                    Ok(load)
+                })
+        }))
+}
+
+fn version<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+) -> Box<dyn Future<Item = NodeVersion, Error = Error> + Send> {
+    let request = create_request(Method::GET, "/version", None, host, password);
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    Box::new(run_request_raw(client, request).and_then(|body| {
+        String::from_utf8(body)?.parse()
+    }))
+}
+
+fn info<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+) -> Box<dyn Future<Item = NodeInfo, Error = Error> + Send> {
+    let request = create_request(Method::GET, "/v4/info", None, host, password);
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    run_request(client, request)
+}
+
+fn route_planner_status<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+) -> Box<dyn Future<Item = Option<RoutePlannerStatus>, Error = Error> + Send> {
+    let request = create_request(
+        Method::GET,
+        route_planner::STATUS_URI,
+        None,
+        host,
+        password,
+    );
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    Box::new(run_request_raw(client, request).and_then(|body| {
+        route_planner::parse_status(&body)
+    }))
+}
+
+fn update_session<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+    session_id: &str,
+    session: &Session,
+) -> Box<dyn Future<Item = Session, Error = Error> + Send> {
+    let body = match serde_json::to_vec(session) {
+        Ok(body) => body,
+        Err(why) => return Box::new(future::err(Error::Json(why))),
+    };
+    let body = (body, HeaderValue::from_static("application/json"));
+    let uri = Session::uri(session_id);
+
+    let request = create_request(
+        Method::PATCH,
+        uri.as_ref(),
+        Some(body),
+        host,
+        password,
+    );
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    run_request(client, request)
+}
+
+fn update_player<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+    session_id: &str,
+    guild_id: &str,
+    update: &PlayerUpdateRequest,
+) -> Box<dyn Future<Item = Player, Error = Error> + Send> {
+    let body = match serde_json::to_vec(update) {
+        Ok(body) => body,
+        Err(why) => return Box::new(future::err(Error::Json(why))),
+    };
+    let body = (body, HeaderValue::from_static("application/json"));
+    let uri = update.uri(session_id, guild_id);
+
+    let request = create_request(
+        Method::PATCH,
+        uri.as_ref(),
+        Some(body),
+        host,
+        password,
+    );
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
     run_request(client, request)
 }
 
+#[cfg(feature = "plugin-lavasearch")]
+fn search_advanced<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+    query: &str,
+    types: &[SearchType],
+) -> Box<dyn Future<Item = SearchResult, Error = Error> + Send> {
+    let uri = lavasearch::search_uri(query, types);
+    let request = create_request(
+        Method::GET,
+        uri.as_ref(),
+        None,
+        host,
+        password,
+    );
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    Box::new(run_request_with_status(client, request)
+        .and_then(|(status, body)| lavasearch::parse_search(status, body)))
+}
+
+#[cfg(feature = "plugin-lavalyrics")]
+fn lyrics<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+    uri: &str,
+) -> Box<dyn Future<Item = Option<Lyrics>, Error = Error> + Send> {
+    let request = match create_request(Method::GET, uri, None, host, password) {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    Box::new(run_request_with_status(client, request)
+        .and_then(|(status, body)| lavalyrics::parse_lyrics(status, body)))
+}
+
+#[cfg(feature = "plugin-lavalyrics")]
+fn set_lyrics_subscription<S: SendRequest>(
+    client: &S,
+    host: &str,
+    password: &[u8],
+    session_id: &str,
+    guild_id: &str,
+    subscribed: bool,
+) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+    let method = if subscribed {
+        Method::POST
+    } else {
+        Method::DELETE
+    };
+    let uri = lavalyrics::subscription_uri(session_id, guild_id);
+    let request = create_request(
+        method,
+        uri.as_ref(),
+        None,
+        host,
+        password,
+    );
+    let request = match request {
+        Ok(v) => v,
+        Err(why) => return Box::new(future::err(why)),
+    };
+
+    Box::new(run_request_raw(client, request).map(|_| ()))
+}
+
 fn create_request(
     method: Method,
     uri: &str,
@@ -193,18 +1815,177 @@ fn create_request(
     Ok(req)
 }
 
-fn run_request<C, T>(client: &Client<C, Body>, request: Request<Body>)
-    -> Box<Future<Item = T, Error = Error> + Send>
-    where C: Connect + 'static,
+fn run_request<S, T>(client: &S, request: Request<Body>)
+    -> Box<dyn Future<Item = T, Error = Error> + Send>
+    where S: SendRequest,
           T: DeserializeOwned + Send + Sized + 'static {
-    Box::new(client.request(request)
-        .and_then(|res| res.into_body().concat2())
-        .from_err::<Error>()
-        .map(|body| {
-            debug!("Body: {}", String::from_utf8_lossy(&body));
+    Box::new(run_request_raw(client, request)
+        .and_then(|body| serde_json::from_slice::<T>(&body).map_err(From::from)))
+}
+
+/// Runs a request, resolving to its body, or to an [`Error::Status`] if the
+/// node responded with an unsuccessful status.
+///
+/// [`Error::Status`]: ../../enum.Error.html#variant.Status
+fn run_request_raw<S: SendRequest>(
+    client: &S,
+    request: Request<Body>,
+) -> Box<dyn Future<Item = Vec<u8>, Error = Error> + Send> {
+    Box::new(run_request_with_status(client, request)
+        .and_then(|(status, body)| super::check_status(status, body)))
+}
+
+/// Runs a request, resolving to the status and body of the response whether
+/// or not it was successful.
+fn run_request_with_status<S: SendRequest>(
+    client: &S,
+    request: Request<Body>,
+) -> Box<dyn Future<Item = (u16, Vec<u8>), Error = Error> + Send> {
+    Box::new(client.send_request(request)
+        .and_then(|res| {
+            let status = res.status().as_u16();
 
-            body
+            res.into_body().concat2().map(move |body| (status, body))
         })
-        .and_then(|body| serde_json::from_slice::<T>(&body).map_err(From::from))
-        .from_err())
+        .from_err::<Error>()
+        .map(|(status, body)| {
+            debug!("Status: {}, body: {}", status, String::from_utf8_lossy(&body));
+
+            (status, body.to_vec())
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use futures::Future;
+    use super::{RestClient, TrackCounter};
+    use super::super::stub::{self, CountingNode, StubNode};
+
+    #[test]
+    fn test_request_id_header() {
+        let node = StubNode::serve("200 OK", "4.0.0");
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+
+        let request = client.version();
+        // the request isn't timed until it's sent
+        assert!(client.metrics().endpoint("version").is_none());
+
+        stub::block_on(request).unwrap();
+        assert_eq!(client.metrics().endpoint("version").unwrap().count, 1);
+        assert!(node.request().contains("x-request-id: "));
+    }
+
+    #[test]
+    fn test_unsuccessful_status() {
+        let node = StubNode::serve("401 Unauthorized", "Unauthorized");
+        let client = RestClient::new(&node.host, "wrong_password").unwrap();
+
+        match stub::block_on(client.version()) {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 401),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[test]
+    fn test_lyrics_not_found() {
+        let node = StubNode::serve("404 Not Found", "Lyrics not found");
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+
+        let lyrics = stub::block_on(client.lyrics("QAAAjQIAJVJpY2sgQXN0bGV5", false));
+        assert!(lyrics.unwrap().is_none());
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[test]
+    fn test_lyrics_subscription_status() {
+        let node = StubNode::serve("404 Not Found", "Player not found");
+        let mut client = RestClient::new(&node.host, "test_password").unwrap();
+        client.set_session_id("la3kfsdf5eafe848");
+
+        match stub::block_on(client.set_lyrics_subscription("1", true)) {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 404),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_shared_loads() {
+        let node = CountingNode::serve(
+            "200 OK",
+            r#"{"loadType":"NO_MATCHES","playlistInfo":{},"tracks":[]}"#,
+        );
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+
+        let loads = client.load_tracks("ytsearch:a").join(client.load_tracks("ytsearch:a"));
+        stub::block_on(loads).unwrap();
+        assert_eq!(node.requests(), 1);
+        assert!(client.in_flight.lock().is_empty());
+
+        // a load that every caller dropped isn't shared with later loads
+        drop(client.load_tracks("ytsearch:b"));
+        assert!(client.in_flight.lock().is_empty());
+        stub::block_on(client.load_tracks("ytsearch:b")).unwrap();
+        assert_eq!(node.requests(), 2);
+    }
+
+    #[test]
+    fn test_shared_load_errors() {
+        let node = CountingNode::serve("500 Internal Server Error", "");
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+
+        match stub::block_on(client.load_tracks("ytsearch:a")) {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 500),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let loads = client.load_tracks("ytsearch:a")
+            .then(Ok::<_, ()>)
+            .join(client.load_tracks("ytsearch:a").then(Ok));
+
+        let (first, second) = stub::block_on(loads).unwrap();
+        assert!(matches!(first, Err(Error::Shared(_))), "{:?}", first);
+        assert!(matches!(second, Err(Error::Shared(_))), "{:?}", second);
+        assert_eq!(node.requests(), 2);
+    }
+
+    #[test]
+    fn test_hyper_errors_retryable() {
+        let client = RestClient::new(stub::closed_host(), "test_password").unwrap();
+        let error = stub::block_on(client.version()).unwrap_err();
+        assert!(error.is_retryable(), "refused connection: {:?}", error);
+
+        // the connection closes before the body is complete
+        let node = StubNode::serve_raw(
+            "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n4.0".to_owned(),
+        );
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+        let error = stub::block_on(client.version()).unwrap_err();
+        assert!(error.is_retryable(), "incomplete body: {:?}", error);
+
+        let node = StubNode::serve_raw("not a response\r\n\r\n".to_owned());
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+        let error = stub::block_on(client.version()).unwrap_err();
+        assert!(!error.is_retryable(), "invalid response: {:?}", error);
+    }
+
+    #[test]
+    fn test_track_counter() {
+        let body = br#"{"loadType":"PLAYLIST_LOADED","playlistInfo":{"tracks":[{}]},"tracks":[{"track":"QAAA","info":{"title":"}\"{","tags":[{}]}},{"track":"QAAB","info":{}}]}"#;
+
+        // the count must be the same however the response is split
+        for size in 1..body.len() {
+            let mut counter = TrackCounter::default();
+            let count = body.chunks(size).map(|chunk| counter.feed(chunk)).last();
+
+            assert_eq!(count, Some(2), "chunks of {}", size);
+        }
+
+        let mut counter = TrackCounter::default();
+        let second = body.windows(6).position(|w| w == b"\"QAAB\"").unwrap();
+        let (first, rest) = body.split_at(second);
+        assert_eq!(counter.feed(first), 1);
+        assert_eq!(counter.feed(rest), 2);
+    }
 }
@@ -0,0 +1,293 @@
+//! Models for the [LavaLyrics] plugin's lyrics endpoints and events.
+//!
+//! The plugin is only available for Lavalink v4 nodes. To enable support for
+//! it, add the `plugin-lavalyrics` feature to your `Cargo.toml`.
+//!
+//! Live lyrics events are only sent for players which were subscribed to
+//! them. They're sent with the `event` opcode and a type that isn't known to
+//! [`Event`], so parse them from the raw value of an
+//! [`IncomingMessage::Unknown`]:
+//!
+//! ```rust
+//! # fn main() -> lavalink::Result<()> {
+//! use lavalink::model::{IncomingMessage, ParseMode};
+//! use lavalink::rest::lavalyrics::LyricsEvent;
+//! use serde_json;
+//!
+//! let payload = r#"{"op":"event","type":"LyricsNotFoundEvent","guildId":"1"}"#;
+//!
+//! if let IncomingMessage::Unknown(value) = IncomingMessage::parse(
+//!     payload,
+//!     ParseMode::Lenient,
+//! )? {
+//!     let event = serde_json::from_value::<LyricsEvent>(value)?;
+//!     assert_eq!(event.guild_id(), "1");
+//! }
+//! #     Ok(())
+//! # }
+//! ```
+//!
+//! [LavaLyrics]: https://github.com/topi314/LavaLyrics
+//! [`Event`]: ../../model/enum.Event.html
+//! [`IncomingMessage::Unknown`]: ../../model/enum.IncomingMessage.html#variant.Unknown
+
+use crate::model::{deserialize_guild_id, duration_from_millis};
+use serde_json::Value;
+use std::time::Duration;
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use crate::Result;
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use super::{check_status, encode_query_component};
+
+/// The lyrics of a track.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lyrics {
+    /// The timed lines of the lyrics, if the provider has them.
+    #[serde(default)]
+    pub lines: Option<Vec<LyricsLine>>,
+    /// Additional information provided by other plugins.
+    #[serde(default)]
+    pub plugin: Value,
+    /// The provider of the lyrics, such as a website.
+    pub provider: String,
+    /// The name of the source the lyrics were retrieved from.
+    pub source_name: String,
+    /// The full text of the lyrics, if the provider has it.
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// A timed line of a track's lyrics.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LyricsLine {
+    /// How long the line lasts in milliseconds, if known.
+    #[serde(default)]
+    pub duration: Option<i64>,
+    /// The text of the line.
+    pub line: String,
+    /// Additional information provided by other plugins.
+    #[serde(default)]
+    pub plugin: Value,
+    /// The position in the track at which the line starts, in milliseconds.
+    pub timestamp: i64,
+}
+
+impl LyricsLine {
+    /// Returns how long the line lasts, if known.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration.map(duration_from_millis)
+    }
+
+    /// Returns the position in the track at which the line starts.
+    pub fn timestamp(&self) -> Duration {
+        duration_from_millis(self.timestamp)
+    }
+}
+
+/// A live lyrics event for a subscribed player.
+///
+/// **Note**: This is only sent from a node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum LyricsEvent {
+    /// Lyrics were found for the player's new track.
+    #[serde(rename = "LyricsFoundEvent")]
+    Found(LyricsFoundEvent),
+    /// The player reached a new line of the lyrics.
+    #[serde(rename = "LyricsLineEvent")]
+    Line(LyricsLineEvent),
+    /// No lyrics were found for the player's new track.
+    #[serde(rename = "LyricsNotFoundEvent")]
+    NotFound(LyricsNotFoundEvent),
+}
+
+impl LyricsEvent {
+    /// The event types of live lyrics events, as in an event's `type` field.
+    pub const TYPES: &'static [&'static str] = &[
+        "LyricsFoundEvent",
+        "LyricsLineEvent",
+        "LyricsNotFoundEvent",
+    ];
+
+    /// Returns the guild ID of the event.
+    pub fn guild_id(&self) -> &str {
+        match self {
+            LyricsEvent::Found(e) => &e.guild_id,
+            LyricsEvent::Line(e) => &e.guild_id,
+            LyricsEvent::NotFound(e) => &e.guild_id,
+        }
+    }
+}
+
+/// Lyrics were found for a player's new track.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsFoundEvent {
+    /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
+    pub guild_id: String,
+    /// The lyrics that were found.
+    pub lyrics: Lyrics,
+}
+
+/// A player reached a new line of its track's lyrics.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsLineEvent {
+    /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
+    pub guild_id: String,
+    /// The line that was reached.
+    pub line: LyricsLine,
+    /// The index of the line in the lyrics' [`lines`].
+    ///
+    /// [`lines`]: struct.Lyrics.html#structfield.lines
+    pub line_index: i64,
+    /// Whether the line was skipped, such as by seeking past it.
+    pub skipped: bool,
+}
+
+/// No lyrics were found for a player's new track.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LyricsNotFoundEvent {
+    /// The guild ID of the affected player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
+    pub guild_id: String,
+}
+
+/// Creates the URI path for retrieving the lyrics of an encoded track.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn lyrics_uri(track: &str, skip_track_source: bool) -> String {
+    format!(
+        "/v4/lyrics?track={}&skipTrackSource={}",
+        encode_query_component(track),
+        skip_track_source,
+    )
+}
+
+/// Creates the URI path for retrieving the lyrics of a player's current
+/// track.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn player_lyrics_uri(
+    session_id: &str,
+    guild_id: &str,
+    skip_track_source: bool,
+) -> String {
+    format!(
+        "/v4/sessions/{}/players/{}/track/lyrics?skipTrackSource={}",
+        session_id,
+        guild_id,
+        skip_track_source,
+    )
+}
+
+/// Creates the URI path for subscribing a player to live lyrics events.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn subscription_uri(session_id: &str, guild_id: &str) -> String {
+    format!(
+        "/v4/sessions/{}/players/{}/lyrics/subscribe",
+        session_id,
+        guild_id,
+    )
+}
+
+/// Parses the response to a lyrics request.
+///
+/// The node responds with no content or as not found if it found no lyrics.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn parse_lyrics(status: u16, body: Vec<u8>) -> Result<Option<Lyrics>> {
+    if status == 204 || status == 404 {
+        return Ok(None);
+    }
+
+    let body = check_status(status, body)?;
+
+    serde_json::from_slice(&body).map(Some).map_err(From::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use std::time::Duration;
+    use super::*;
+
+    static LINE_EVENT: &str = r#"{
+  "op": "event",
+  "type": "LyricsLineEvent",
+  "guildId": "1",
+  "lineIndex": 2,
+  "line": {
+    "timestamp": 12500,
+    "duration": 3000,
+    "line": "example line",
+    "plugin": {}
+  },
+  "skipped": false
+}"#;
+
+    static LYRICS: &str = r#"{
+  "sourceName": "youtube",
+  "provider": "example provider",
+  "text": null,
+  "lines": [
+    {
+      "timestamp": 0,
+      "duration": null,
+      "line": "first line",
+      "plugin": {}
+    }
+  ],
+  "plugin": {}
+}"#;
+
+    #[test]
+    fn test_lyrics_deser() {
+        let lyrics = serde_json::from_str::<Lyrics>(LYRICS).unwrap();
+        assert!(lyrics.text.is_none());
+
+        let line = &lyrics.lines.unwrap()[0];
+        assert_eq!(line.line, "first line");
+        assert!(line.duration().is_none());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_parse_lyrics() {
+        use crate::Error;
+
+        assert!(parse_lyrics(204, Vec::new()).unwrap().is_none());
+        assert!(parse_lyrics(404, b"Lyrics not found".to_vec()).unwrap().is_none());
+
+        let lyrics = parse_lyrics(200, LYRICS.as_bytes().to_vec()).unwrap();
+        assert_eq!(lyrics.unwrap().provider, "example provider");
+
+        // an error body mustn't be parsed as lyrics
+        match parse_lyrics(500, b"Internal Server Error".to_vec()) {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 500),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_line_event_deser() {
+        match serde_json::from_str::<LyricsEvent>(LINE_EVENT).unwrap() {
+            LyricsEvent::Line(event) => {
+                assert_eq!(event.line_index, 2);
+                assert_eq!(event.line.timestamp(), Duration::from_millis(12500));
+            },
+            other => panic!("parsed as {:?}", other),
+        }
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_lyrics_uri() {
+        assert_eq!(
+            lyrics_uri("QAAA+w==", true),
+            "/v4/lyrics?track=QAAA%2Bw%3D%3D&skipTrackSource=true",
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Models for the [LavaSearch] plugin's advanced search endpoint.
+//!
+//! The plugin is only available for Lavalink v4 nodes. To enable support for
+//! it, add the `plugin-lavasearch` feature to your `Cargo.toml`.
+//!
+//! [LavaSearch]: https://github.com/topi314/LavaSearch
+
+use serde_json::Value;
+use super::LoadedTrack;
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use crate::Result;
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use super::{check_status, encode_query_component};
+
+/// A type of result that can be requested from the advanced search endpoint.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchType {
+    /// Albums matching the query.
+    Album,
+    /// Artists matching the query.
+    Artist,
+    /// Playlists matching the query.
+    Playlist,
+    /// Text suggestions for the query.
+    Text,
+    /// Tracks matching the query.
+    Track,
+}
+
+impl SearchType {
+    /// Returns the name of the type as used in the `types` query parameter.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchType::Album => "album",
+            SearchType::Artist => "artist",
+            SearchType::Playlist => "playlist",
+            SearchType::Text => "text",
+            SearchType::Track => "track",
+        }
+    }
+}
+
+/// The results of an advanced search, split by the type of the result.
+///
+/// Types which were not requested are empty. When the node finds nothing
+/// all lists are empty.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SearchResult {
+    /// The albums that were found.
+    pub albums: Vec<SearchPlaylist>,
+    /// The artists that were found.
+    pub artists: Vec<SearchPlaylist>,
+    /// The playlists that were found.
+    pub playlists: Vec<SearchPlaylist>,
+    /// Additional information provided by other plugins.
+    pub plugin: Value,
+    /// The text suggestions that were found.
+    pub texts: Vec<SearchText>,
+    /// The tracks that were found.
+    pub tracks: Vec<LoadedTrack>,
+}
+
+/// An album, artist or playlist returned by an advanced search.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPlaylist {
+    /// Information about the album, artist or playlist.
+    pub info: SearchPlaylistInfo,
+    /// Additional information provided by other plugins.
+    #[serde(default)]
+    pub plugin_info: Value,
+    /// The tracks of the album, artist or playlist, if any were loaded.
+    #[serde(default)]
+    pub tracks: Vec<LoadedTrack>,
+}
+
+/// Information about an album, artist or playlist.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPlaylistInfo {
+    /// The name of the album, artist or playlist.
+    pub name: String,
+    /// The index of the selected track, or `-1` if none is selected.
+    pub selected_track: i64,
+}
+
+/// A text suggestion returned by an advanced search.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchText {
+    /// Additional information provided by other plugins.
+    #[serde(default)]
+    pub plugin: Value,
+    /// The suggested text.
+    pub text: String,
+}
+
+/// Creates the URI path for an advanced search.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn search_uri(
+    query: &str,
+    types: &[SearchType],
+) -> String {
+    let types = types
+        .iter()
+        .map(|kind| kind.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        "/v4/loadsearch?query={}&types={}",
+        encode_query_component(query),
+        types,
+    )
+}
+
+/// Parses the response to an advanced search.
+///
+/// The node responds with no content if nothing was found.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn parse_search(status: u16, body: Vec<u8>) -> Result<SearchResult> {
+    if status == 204 {
+        return Ok(SearchResult::default());
+    }
+
+    let body = check_status(status, body)?;
+
+    serde_json::from_slice(&body).map_err(From::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::*;
+
+    static SEARCH: &str = r#"{
+  "tracks": [],
+  "albums": [
+    {
+      "info": {
+        "name": "Example Album",
+        "selectedTrack": -1
+      },
+      "pluginInfo": {},
+      "tracks": []
+    }
+  ],
+  "artists": [],
+  "playlists": [],
+  "texts": [
+    {
+      "text": "example",
+      "plugin": {}
+    }
+  ],
+  "plugin": {}
+}"#;
+
+    #[test]
+    fn test_search_result_deser() {
+        let result = serde_json::from_str::<SearchResult>(SEARCH).unwrap();
+        assert_eq!(result.albums[0].info.name, "Example Album");
+        assert_eq!(result.texts[0].text, "example");
+        assert!(result.tracks.is_empty());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_search_uri() {
+        let uri = search_uri("ytsearch:foo", &[
+            SearchType::Track,
+            SearchType::Album,
+        ]);
+        assert_eq!(
+            uri,
+            "/v4/loadsearch?query=ytsearch%3Afoo&types=track,album",
+        );
+
+        // reserved characters can't end the query or add parameters
+        let uri = search_uri("ytsearch:simon & garfunkel+1=#2", &[
+            SearchType::Track,
+        ]);
+        assert_eq!(
+            uri,
+            "/v4/loadsearch?query=ytsearch%3Asimon%20%26%20garfunkel%2B1%3D%232&types=track",
+        );
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_parse_search() {
+        use crate::Error;
+
+        let result = parse_search(204, Vec::new()).unwrap();
+        assert!(result.tracks.is_empty());
+
+        let result = parse_search(200, SEARCH.as_bytes().to_vec()).unwrap();
+        assert_eq!(result.texts[0].text, "example");
+
+        match parse_search(401, b"Unauthorized".to_vec()) {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 401),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Latency statistics for requests made by the owned REST clients.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The upper bounds of the latency histogram buckets, in milliseconds.
+///
+/// Requests slower than the last bound are counted in a final bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [10, 25, 50, 100, 250, 500, 1000, 2500];
+
+/// The header that the ID of a request made by the owned clients is sent in,
+/// so that it can be matched with the node's logs.
+pub(crate) const REQUEST_ID_HEADER: &str = "x-request-id";
+
+static NEXT_REQUEST_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// Returns a process-unique ID for a new request.
+pub(crate) fn next_request_id() -> usize {
+    NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Latency statistics for a single REST endpoint.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EndpointMetrics {
+    /// The number of requests made to the endpoint.
+    pub count: u64,
+    /// The number of requests to the endpoint that returned an error.
+    pub errors: u64,
+    /// The number of requests in each latency bucket.
+    ///
+    /// The bucket at index `i` counts requests that took at most
+    /// [`LATENCY_BUCKETS_MS`]`[i]` milliseconds and more than the previous
+    /// bound. The last bucket counts all slower requests.
+    ///
+    /// [`LATENCY_BUCKETS_MS`]: constant.LATENCY_BUCKETS_MS.html
+    pub histogram: [u64; 9],
+    /// The latency of the slowest request.
+    pub max: Duration,
+    /// The latency of the fastest request.
+    pub min: Duration,
+    /// The total latency of all requests.
+    pub total: Duration,
+}
+
+impl EndpointMetrics {
+    /// Returns the average latency of requests to the endpoint.
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::from_secs(0);
+        }
+
+        let nanos = self.total.as_secs() as u128 * 1_000_000_000
+            + u128::from(self.total.subsec_nanos());
+        let average = nanos / u128::from(self.count);
+
+        Duration::new(
+            (average / 1_000_000_000) as u64,
+            (average % 1_000_000_000) as u32,
+        )
+    }
+
+    fn record(&mut self, latency: Duration, success: bool) {
+        if self.count == 0 || latency < self.min {
+            self.min = latency;
+        }
+        if latency > self.max {
+            self.max = latency;
+        }
+
+        self.count += 1;
+        self.total += latency;
+
+        if !success {
+            self.errors += 1;
+        }
+
+        let millis = latency.as_secs() * 1000
+            + u64::from(latency.subsec_millis());
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| millis <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.histogram[bucket] += 1;
+    }
+}
+
+/// Latency statistics for the REST endpoints used by a client, keyed by the
+/// name of the endpoint such as `"loadtracks"`.
+#[derive(Debug, Default)]
+pub struct RestMetrics {
+    endpoints: Mutex<HashMap<&'static str, EndpointMetrics>>,
+}
+
+impl RestMetrics {
+    /// Returns the statistics of a single endpoint, if a request was made to
+    /// it.
+    pub fn endpoint(&self, endpoint: &str) -> Option<EndpointMetrics> {
+        self.lock().get(endpoint).cloned()
+    }
+
+    /// Returns the statistics of all endpoints that requests were made to.
+    pub fn snapshot(&self) -> HashMap<&'static str, EndpointMetrics> {
+        self.lock().clone()
+    }
+
+    /// Clears all statistics.
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    /// Starts timing a request to an endpoint, which should be done when the
+    /// request is sent.
+    pub(crate) fn start(&self, endpoint: &'static str, id: usize) -> RequestTimer {
+        debug!("Starting request {} to {}", id, endpoint);
+
+        RequestTimer {
+            endpoint,
+            id,
+            started: Instant::now(),
+        }
+    }
+
+    /// Records the result of a timed request.
+    pub(crate) fn finish(&self, timer: RequestTimer, success: bool) {
+        let latency = timer.started.elapsed();
+        debug!(
+            "Request {} to {} finished in {:?} (success: {})",
+            timer.id,
+            timer.endpoint,
+            latency,
+            success,
+        );
+
+        self.lock()
+            .entry(timer.endpoint)
+            .or_default()
+            .record(latency, success);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<&'static str, EndpointMetrics>> {
+        // The map is always left in a consistent state, so a panic while
+        // holding the lock doesn't invalidate it.
+        self.endpoints.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A request being timed, identified by a process-unique ID used in logs and
+/// sent to the node.
+#[derive(Debug)]
+pub(crate) struct RequestTimer {
+    endpoint: &'static str,
+    id: usize,
+    started: Instant,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::EndpointMetrics;
+
+    #[test]
+    fn test_record() {
+        let mut metrics = EndpointMetrics::default();
+        metrics.record(Duration::from_millis(5), true);
+        metrics.record(Duration::from_millis(300), false);
+        metrics.record(Duration::from_secs(10), true);
+
+        assert_eq!(metrics.count, 3);
+        assert_eq!(metrics.errors, 1);
+        assert_eq!(metrics.min, Duration::from_millis(5));
+        assert_eq!(metrics.max, Duration::from_secs(10));
+        assert_eq!(metrics.histogram, [1, 0, 0, 0, 0, 1, 0, 0, 1]);
+        assert_eq!(metrics.average(), Duration::from_millis(3435));
+    }
+}
@@ -11,10 +11,40 @@
 //! ]
 //! ```
 
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub mod enrich;
 #[cfg(feature = "hyper")]
 pub mod hyper;
+#[cfg(feature = "plugin-lavalyrics")]
+pub mod lavalyrics;
+#[cfg(feature = "plugin-lavasearch")]
+pub mod lavasearch;
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub mod metrics;
+pub mod registry;
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
+pub mod route_planner;
+#[cfg(all(test, any(feature = "hyper", feature = "reqwest")))]
+mod stub;
+
+use crate::model::{
+    deserialize_guild_id,
+    duration_from_millis,
+    Equalizer,
+    Filters,
+    OutgoingMessage,
+};
+use crate::sanitize::{sanitize, SanitizePolicy};
+use crate::{Error, Result};
+use serde::de::{Deserialize, Deserializer};
+use serde_json::{self, Value};
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::result::Result as StdResult;
+use std::str::FromStr;
+use std::time::Duration;
 
 /// Information about loaded tracks.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -28,8 +58,136 @@ pub struct Load {
     pub tracks: Vec<LoadedTrack>,
 }
 
+impl Load {
+    /// Returns whether the load found any tracks.
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    pub(crate) fn found_tracks(&self) -> bool {
+        match self.load_type {
+            LoadType::LoadFailed | LoadType::NoMatches => false,
+            _ => !self.tracks.is_empty(),
+        }
+    }
+}
+
+/// The result of loading tracks with a chain of fallback sources, such as
+/// with the Hyper client's [`load_with_fallback`].
+///
+/// [`load_with_fallback`]: hyper/struct.RestClient.html#method.load_with_fallback
+#[derive(Clone, Debug)]
+pub struct FallbackLoad {
+    /// The last load that was made.
+    ///
+    /// This is the first load that found tracks, or the last fallback's if
+    /// none did.
+    pub load: Load,
+    /// The source that was searched for the load, or `None` if it is the
+    /// load of the original identifier.
+    pub source: Option<Source>,
+}
+
+/// A source that tracks can be searched on, by a node with its source manager
+/// enabled.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Source {
+    /// SoundCloud.
+    SoundCloud,
+    /// YouTube.
+    YouTube,
+    /// YouTube Music.
+    YouTubeMusic,
+}
+
+impl Source {
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    const ALL: &'static [Source] = &[
+        Source::SoundCloud,
+        Source::YouTube,
+        Source::YouTubeMusic,
+    ];
+
+    /// Returns the prefix of identifiers that search the source.
+    pub fn search_prefix(self) -> &'static str {
+        match self {
+            Source::SoundCloud => "scsearch:",
+            Source::YouTube => "ytsearch:",
+            Source::YouTubeMusic => "ytmsearch:",
+        }
+    }
+
+    /// Creates the identifier to search the source for a query.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::rest::Source;
+    ///
+    /// assert_eq!(Source::SoundCloud.search("lofi"), "scsearch:lofi");
+    /// ```
+    pub fn search(self, query: impl AsRef<str>) -> String {
+        format!("{}{}", self.search_prefix(), query.as_ref())
+    }
+}
+
+/// The loads to make for loading tracks with fallback sources.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) struct FallbackPlan {
+    /// The identifier to load first.
+    pub(crate) primary: String,
+    /// The query to search the fallback sources for.
+    query: Option<String>,
+}
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+impl FallbackPlan {
+    pub(crate) fn new(identifier: &str) -> Self {
+        let search = Source::ALL.iter().find_map(|source| {
+            identifier.strip_prefix(source.search_prefix())
+        });
+
+        if let Some(query) = search {
+            return Self {
+                primary: identifier.to_owned(),
+                query: Some(query.to_owned()),
+            };
+        }
+
+        // an encoded track can't be loaded itself, so its URL is loaded and
+        // its title and author are searched for
+        match crate::decoder::decode_track_base64(identifier) {
+            Ok(track) => Self {
+                primary: track.url.unwrap_or_else(|| identifier.to_owned()),
+                query: Some(format!("{} {}", track.author, track.title)),
+            },
+            Err(_) => Self {
+                primary: identifier.to_owned(),
+                query: None,
+            },
+        }
+    }
+
+    /// Returns the identifiers to load, in order, if loading the primary
+    /// identifier finds no tracks.
+    ///
+    /// Searches that are the same as the primary identifier are skipped.
+    pub(crate) fn fallbacks(
+        &self,
+        sources: impl IntoIterator<Item = Source>,
+    ) -> Vec<(Source, String)> {
+        let query = match self.query {
+            Some(ref query) => query,
+            None => return Vec::new(),
+        };
+
+        sources.into_iter()
+            .map(|source| (source, source.search(query)))
+            .filter(|(_, identifier)| *identifier != self.primary)
+            .collect()
+    }
+}
+
 /// The type of a track load.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[non_exhaustive]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LoadType {
     /// Indicator that loading the track failed.
@@ -52,7 +210,7 @@ pub struct LoadedTrackInfo {
     pub title: String,
     /// The name of the author of the track.
     pub author: String,
-    /// The length of the track in frames.
+    /// The length of the track in milliseconds.
     pub length: i64,
     /// The ID of the track.
     pub identifier: String,
@@ -62,25 +220,1317 @@ pub struct LoadedTrackInfo {
     pub is_stream: bool,
     /// Whether the track can be seeked.
     pub is_seekable: bool,
-    /// The current position in the track.
+    /// The current position in the track in milliseconds.
     pub position: i64,
+    /// The URL of the track's artwork, such as its album art.
+    ///
+    /// This is only sent by Lavalink v4 nodes, and only by sources that
+    /// provide artwork.
+    #[serde(default)]
+    pub artwork_url: Option<String>,
+    /// The International Standard Recording Code of the track.
+    ///
+    /// This is only sent by Lavalink v4 nodes, and only by sources that
+    /// provide it.
+    #[serde(default)]
+    pub isrc: Option<String>,
+}
+
+impl LoadedTrackInfo {
+    /// Returns the title of the track, cleaned up for display according to a
+    /// policy.
+    ///
+    /// See [`sanitize`] for an example.
+    ///
+    /// [`sanitize`]: ../sanitize/fn.sanitize.html
+    pub fn sanitized_title(&self, policy: &SanitizePolicy) -> String {
+        sanitize(&self.title, policy)
+    }
+
+    /// Returns the name of the author of the track, cleaned up for display
+    /// according to a policy.
+    pub fn sanitized_author(&self, policy: &SanitizePolicy) -> String {
+        sanitize(&self.author, policy)
+    }
+
+    /// Returns the length of the track.
+    ///
+    /// Nodes report the length of streams as `i64::MAX` milliseconds.
+    pub fn length(&self) -> Duration {
+        duration_from_millis(self.length)
+    }
+
+    /// Returns the current position in the track.
+    pub fn position(&self) -> Duration {
+        duration_from_millis(self.position)
+    }
 }
 
 /// Information about a track.
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct LoadedTrack {
     /// Base64 encoded representation of the track.
+    ///
+    /// Lavalink v4 nodes name this field `encoded`.
+    #[serde(alias = "encoded")]
     pub track: String,
     /// Meta information about the track.
     pub info: LoadedTrackInfo,
+    /// Additional information about the track provided by node plugins.
+    ///
+    /// This is only sent by Lavalink v4 nodes, and is `Value::Null`
+    /// otherwise.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub plugin_info: Value,
+}
+
+impl LoadedTrack {
+    /// Returns a short identifier of the track, made of 16 hex digits.
+    ///
+    /// This is a hash of the [`track`] blob that is the same across processes
+    /// and versions of this crate, so it can be put in places with a length
+    /// limit, such as the custom IDs of message components. Different tracks
+    /// may have the same hash; use a [`TrackRegistry`] to look tracks up by
+    /// their identifiers.
+    ///
+    /// [`TrackRegistry`]: registry/struct.TrackRegistry.html
+    /// [`track`]: #structfield.track
+    pub fn short_hash(&self) -> String {
+        format!("{:016x}", registry::fnv1a(self.track.as_bytes()))
+    }
+}
+
+/// The version of a Lavalink node.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeVersion {
+    /// The full version string.
+    pub semver: String,
+    /// The major version.
+    pub major: u32,
+    /// The minor version.
+    pub minor: u32,
+    /// The patch version.
+    pub patch: u32,
+    /// The pre-release identifier, if any.
+    #[serde(default)]
+    pub pre_release: Option<String>,
+}
+
+impl NodeVersion {
+    /// Returns the features supported by a node of this version.
+    pub fn capabilities(&self) -> NodeCapabilities {
+        NodeCapabilities::from_version(self)
+    }
+
+    /// Returns the features supported by a node of this version, running the
+    /// given server implementation.
+    pub fn capabilities_for(&self, dialect: Dialect) -> NodeCapabilities {
+        NodeCapabilities::from_dialect(dialect, self)
+    }
+}
+
+impl FromStr for NodeVersion {
+    type Err = Error;
+
+    /// Parses a version as returned by a node's `/version` endpoint, such as
+    /// `3.7.8` or `4.0.0-beta.1`.
+    ///
+    /// Missing minor and patch versions are read as `0`, and build metadata,
+    /// such as the `+abc` of `4.0.0+abc`, is ignored.
+    ///
+    /// Development builds report a commit hash followed by `-SNAPSHOT`
+    /// instead of a version. These are read as version `0.0.0`, with the
+    /// whole version as the pre-release identifier.
+    fn from_str(s: &str) -> Result<Self> {
+        let semver = s.trim();
+        let version = semver.split('+').next().unwrap_or_default();
+        let mut parts = version.splitn(2, '-');
+        let mut numbers = parts.next().unwrap_or_default().split('.');
+        let pre_release = parts.next().map(ToOwned::to_owned);
+
+        let mut next = |required: bool| match numbers.next() {
+            Some(number) => number.parse::<u32>().ok(),
+            None if required => None,
+            None => Some(0),
+        };
+
+        match (next(true), next(false), next(false)) {
+            (Some(major), Some(minor), Some(patch)) => Ok(Self {
+                semver: semver.to_owned(),
+                major,
+                minor,
+                patch,
+                pre_release,
+            }),
+            _ if version.ends_with("-SNAPSHOT") => Ok(Self {
+                semver: semver.to_owned(),
+                major: 0,
+                minor: 0,
+                patch: 0,
+                pre_release: Some(version.to_owned()),
+            }),
+            _ => Err(Error::InvalidNodeVersion(s.to_owned())),
+        }
+    }
+}
+
+/// Information about a Lavalink v4 node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    /// The Unix timestamp in milliseconds at which the node was built.
+    pub build_time: u64,
+    /// The names of the filters enabled on the node.
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// The plugins loaded by the node.
+    #[serde(default)]
+    pub plugins: Vec<NodeInfoPlugin>,
+    /// The names of the source managers enabled on the node.
+    #[serde(default)]
+    pub source_managers: Vec<String>,
+    /// The version of the node.
+    pub version: NodeVersion,
+}
+
+impl NodeInfo {
+    /// Returns the features supported by the node.
+    ///
+    /// Unlike [`NodeVersion::capabilities`], this takes into account whether
+    /// the node has any filters enabled.
+    ///
+    /// [`NodeVersion::capabilities`]: struct.NodeVersion.html#method.capabilities
+    pub fn capabilities(&self) -> NodeCapabilities {
+        let mut capabilities = self.version.capabilities();
+        capabilities.filters &= !self.filters.is_empty();
+
+        capabilities
+    }
+}
+
+/// A plugin loaded by a Lavalink v4 node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NodeInfoPlugin {
+    /// The name of the plugin.
+    pub name: String,
+    /// The version of the plugin.
+    pub version: String,
+}
+
+/// The features supported by a node, used to decide which messages and
+/// endpoints can be used with it.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::rest::NodeVersion;
+///
+/// let version = "3.7.8".parse::<NodeVersion>()?;
+/// let capabilities = version.capabilities();
+///
+/// assert!(capabilities.equalizer_op);
+/// assert!(capabilities.filters);
+/// assert!(!capabilities.rest_players);
+/// # Ok::<(), lavalink::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NodeCapabilities {
+    /// Whether the node accepts the [`Equalizer`] WebSocket message.
+    ///
+    /// This was removed in Lavalink v4 in favour of filters.
+    ///
+    /// [`Equalizer`]: ../model/struct.Equalizer.html
+    pub equalizer_op: bool,
+    /// Whether the node supports filters, introduced in Lavalink v3.4.
+    pub filters: bool,
+    /// Whether the node supports the v4 REST session and player endpoints,
+    /// such as [`PlayerUpdateRequest`].
+    ///
+    /// [`PlayerUpdateRequest`]: struct.PlayerUpdateRequest.html
+    pub rest_players: bool,
+}
+
+impl NodeCapabilities {
+    /// Returns the features supported by a node of the given version.
+    pub fn from_version(version: &NodeVersion) -> Self {
+        let v4 = version.major >= 4;
+
+        Self {
+            equalizer_op: !v4,
+            filters: v4 || (version.major == 3 && version.minor >= 4),
+            rest_players: v4,
+        }
+    }
+
+    /// Returns the features supported by a node of the given version,
+    /// running the given server implementation.
+    ///
+    /// For [`Dialect::Lavalink`] this is the same as [`from_version`].
+    ///
+    /// [`Dialect::Lavalink`]: enum.Dialect.html#variant.Lavalink
+    /// [`from_version`]: #method.from_version
+    pub fn from_dialect(dialect: Dialect, version: &NodeVersion) -> Self {
+        match dialect {
+            Dialect::Andesite => Self {
+                equalizer_op: true,
+                filters: true,
+                rest_players: false,
+            },
+            Dialect::Lavalink => Self::from_version(version),
+        }
+    }
+
+    /// Converts equalizer settings into the form of update supported by the
+    /// node.
+    ///
+    /// Nodes that still accept the [`Equalizer`] message are sent it
+    /// unchanged, while Lavalink v4 nodes are sent the bands as filters in a
+    /// [`PlayerUpdateRequest`]. Note that the latter replaces all of the
+    /// player's other filters; use [`PlayerUpdateRequest::filters`] with the
+    /// player's current [`Filters`] to keep them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use lavalink::model::{Band, Equalizer};
+    /// use lavalink::rest::{EqualizerUpdate, NodeVersion};
+    ///
+    /// let capabilities = "4.0.0".parse::<NodeVersion>()?.capabilities();
+    /// let equalizer = Equalizer::new("381880193251409931", vec![
+    ///     Band::new(0, 0.25)?,
+    /// ]);
+    ///
+    /// match capabilities.equalizer_update(equalizer) {
+    ///     EqualizerUpdate::Message(message) => {
+    ///         // send the message over the WebSocket connection
+    ///     },
+    ///     EqualizerUpdate::Rest(update) => {
+    ///         // send the update with a RestClient's `update_player`
+    ///     },
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Equalizer`]: ../model/struct.Equalizer.html
+    /// [`Filters`]: ../model/struct.Filters.html
+    /// [`PlayerUpdateRequest`]: struct.PlayerUpdateRequest.html
+    /// [`PlayerUpdateRequest::filters`]: struct.PlayerUpdateRequest.html#method.filters
+    pub fn equalizer_update(&self, equalizer: Equalizer) -> EqualizerUpdate {
+        if self.equalizer_op {
+            EqualizerUpdate::Message(OutgoingMessage::Equalizer(equalizer))
+        } else {
+            EqualizerUpdate::Rest(PlayerUpdateRequest::new().filters(equalizer))
+        }
+    }
+}
+
+/// The server implementation a node runs, for the differences between
+/// Lavalink and compatible servers that can't be detected from the version.
+///
+/// # Examples
+///
+/// ```rust
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use lavalink::rest::{Dialect, NodeVersion};
+///
+/// // Andesite versions don't follow Lavalink's
+/// let version = "0.20.2".parse::<NodeVersion>()?;
+/// let capabilities = version.capabilities_for(Dialect::Andesite);
+///
+/// assert!(capabilities.filters);
+/// #     Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Dialect {
+    /// [Andesite], which implements Lavalink's v3 WebSocket protocol and
+    /// supports filters, but not the Lavalink v4 REST API.
+    ///
+    /// [Andesite]: https://github.com/natanbc/andesite
+    Andesite,
+    /// Lavalink itself, or a fork that follows its versioning.
+    Lavalink,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect::Lavalink
+    }
+}
+
+/// An equalizer change in the form supported by a node, created with
+/// [`NodeCapabilities::equalizer_update`].
+///
+/// [`NodeCapabilities::equalizer_update`]: struct.NodeCapabilities.html#method.equalizer_update
+#[derive(Clone, Debug)]
+pub enum EqualizerUpdate {
+    /// A WebSocket message to send to the node.
+    Message(OutgoingMessage),
+    /// A player update to send to a Lavalink v4 node's REST API.
+    Rest(PlayerUpdateRequest),
+}
+
+/// A player on a Lavalink v4 node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Player {
+    /// The filters applied to the player.
+    #[serde(default)]
+    pub filters: Filters,
+    /// The ID of the guild of the player.
+    #[serde(deserialize_with = "deserialize_guild_id")]
+    pub guild_id: String,
+    /// Whether the player is paused.
+    pub paused: bool,
+    /// The state of the player.
+    pub state: PlayerState,
+    /// The track currently playing, if any.
+    pub track: Option<LoadedTrack>,
+    /// The voice state of the player.
+    pub voice: PlayerVoiceState,
+    /// The volume of the player, on a scale of 0 to 1000.
+    pub volume: i32,
+}
+
+/// The state of a player on a Lavalink v4 node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerState {
+    /// Whether the node is connected to the voice gateway.
+    pub connected: bool,
+    /// The ping of the node to the Discord voice server in milliseconds, or
+    /// `-1` if not connected.
+    pub ping: i64,
+    /// The position of the current track in milliseconds.
+    pub position: i64,
+    /// The Unix timestamp of the state in milliseconds.
+    pub time: u64,
+}
+
+/// The Discord voice state of a player on a Lavalink v4 node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerVoiceState {
+    /// The voice server endpoint.
+    pub endpoint: String,
+    /// The Discord voice session ID.
+    pub session_id: String,
+    /// The voice token.
+    pub token: String,
+}
+
+impl PlayerVoiceState {
+    /// Creates a new voice state.
+    #[inline]
+    pub fn new(
+        token: impl Into<String>,
+        endpoint: impl Into<String>,
+        session_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            session_id: session_id.into(),
+            token: token.into(),
+        }
+    }
+}
+
+/// The resuming configuration of a session on a Lavalink v4 node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Session {
+    /// Whether the session can be resumed after the connection to the node
+    /// closes.
+    pub resuming: bool,
+    /// The number of seconds after which the session can no longer be
+    /// resumed.
+    pub timeout: u64,
+}
+
+impl Session {
+    /// Creates a new session configuration.
+    pub fn new(resuming: bool, timeout: Duration) -> Self {
+        Self {
+            resuming,
+            timeout: timeout.as_secs(),
+        }
+    }
+
+    /// Returns the duration after which the session can no longer be resumed.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+
+    /// Creates the URI path of the session with the given ID.
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    pub(crate) fn uri(session_id: &str) -> String {
+        format!("/v4/sessions/{}", session_id)
+    }
+}
+
+/// A set of changes to apply to a player on a Lavalink v4 node in a single
+/// request.
+///
+/// Changes are only sent if they were set. Setting the same change more than
+/// once keeps the latest value, so pending changes can be collected and sent
+/// together.
+///
+/// # Examples
+///
+/// Resume playback and lower the volume in one request:
+///
+/// ```rust
+/// use lavalink::rest::PlayerUpdateRequest;
+///
+/// let update = PlayerUpdateRequest::new()
+///     .paused(false)
+///     .volume(80);
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerUpdateRequest {
+    /// The time in milliseconds at which to end the track.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<u64>,
+    /// The filters to apply, replacing all of the player's current filters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filters: Option<Filters>,
+    /// Whether to not replace the current track when playing a new one.
+    ///
+    /// This is sent as a query parameter rather than as part of the body. If
+    /// it isn't set, the current track is replaced.
+    #[serde(skip)]
+    pub no_replace: Option<bool>,
+    /// Whether the player is paused.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+    /// The position in milliseconds to seek to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<u64>,
+    /// The track to play, or the data to attach to the track that is
+    /// playing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<PlayerUpdateTrack>,
+    /// The voice state to connect with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<PlayerVoiceState>,
+    /// The volume, on a scale of 0 to 1000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<i32>,
+}
+
+impl PlayerUpdateRequest {
+    /// Creates a new request with no changes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the base64 encoded track to play.
+    ///
+    /// If [`user_data`] is set in the same request, the track is sent along
+    /// with it.
+    ///
+    /// [`user_data`]: #method.user_data
+    pub fn encoded_track(mut self, track: impl Into<String>) -> Self {
+        self.track.get_or_insert_with(PlayerUpdateTrack::default).encoded =
+            Some(track.into());
+
+        self
+    }
+
+    /// Sets the time in milliseconds at which to end the track.
+    pub fn end_time(mut self, end_time: u64) -> Self {
+        self.end_time = Some(end_time);
+
+        self
+    }
+
+    /// Sets the filters to apply, replacing all of the player's current
+    /// filters.
+    pub fn filters(mut self, filters: impl Into<Filters>) -> Self {
+        self.filters = Some(filters.into());
+
+        self
+    }
+
+    /// Sets whether to not replace the current track when playing a new one.
+    pub fn no_replace(mut self, no_replace: bool) -> Self {
+        self.no_replace = Some(no_replace);
+
+        self
+    }
+
+    /// Sets whether the player is paused.
+    pub fn paused(mut self, paused: bool) -> Self {
+        self.paused = Some(paused);
+
+        self
+    }
+
+    /// Sets the position in milliseconds to seek to.
+    pub fn position(mut self, position: u64) -> Self {
+        self.position = Some(position);
+
+        self
+    }
+
+    /// Sets data to attach to the track, which the node echoes back in the
+    /// track's events, such as who requested it.
+    ///
+    /// The data is kept by the node rather than the client, so it's still
+    /// available after the client restarts. Setting it without a track
+    /// replaces the data of the track that is playing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lavalink::rest::PlayerUpdateRequest;
+    /// use serde_json::json;
+    ///
+    /// let update = PlayerUpdateRequest::new()
+    ///     .encoded_track("QAAAjQIAJVJpY2sgQXN0bGV5")
+    ///     .user_data(json!({ "requester": "114941315417899012" }));
+    /// ```
+    pub fn user_data(mut self, user_data: Value) -> Self {
+        self.track.get_or_insert_with(PlayerUpdateTrack::default).user_data =
+            Some(user_data);
+
+        self
+    }
+
+    /// Sets the voice state to connect with.
+    pub fn voice(mut self, voice: PlayerVoiceState) -> Self {
+        self.voice = Some(voice);
+
+        self
+    }
+
+    /// Sets the volume, on a scale of 0 to 1000.
+    pub fn volume(mut self, volume: i32) -> Self {
+        self.volume = Some(volume);
+
+        self
+    }
+
+    /// Applies the changes that are set in another request on top of this
+    /// one, keeping the latest value of each change.
+    ///
+    /// A new track replaces this request's track along with its data, so data
+    /// attached to an earlier track isn't attached to the new one.
+    pub fn merge(&mut self, other: PlayerUpdateRequest) {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(
+                    if other.$field.is_some() {
+                        self.$field = other.$field;
+                    }
+                )*
+            };
+        }
+
+        merge!(end_time, filters, no_replace, paused, position, voice, volume);
+
+        match (self.track.as_mut(), other.track) {
+            // data on its own is attached to whichever track is played
+            (Some(track), Some(PlayerUpdateTrack { encoded: None, user_data })) => {
+                track.user_data = user_data;
+            },
+            (_, Some(track)) => self.track = Some(track),
+            (_, None) => {},
+        }
+    }
+
+    /// Whether no changes are set.
+    pub fn is_empty(&self) -> bool {
+        self.end_time.is_none()
+            && self.filters.is_none()
+            && self.paused.is_none()
+            && self.position.is_none()
+            && self.track.is_none()
+            && self.voice.is_none()
+            && self.volume.is_none()
+    }
+
+    /// Creates the URI path of the request for a guild's player.
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    pub(crate) fn uri(&self, session_id: &str, guild_id: &str) -> String {
+        format!(
+            "/v4/sessions/{}/players/{}?noReplace={}",
+            session_id,
+            guild_id,
+            self.no_replace.unwrap_or(false),
+        )
+    }
+}
+
+/// The track of a [`PlayerUpdateRequest`], along with data attached to it.
+///
+/// [`PlayerUpdateRequest`]: struct.PlayerUpdateRequest.html
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerUpdateTrack {
+    /// The base64 encoded track to play.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoded: Option<String>,
+    /// The data to attach to the track.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_data: Option<Value>,
+}
+
+/// Parses the host of a node, normalizing it into the base URL that request
+/// paths are appended to.
+///
+/// This accepts `host:port`, which is assumed to use HTTP, or an `http://` or
+/// `https://` URL, optionally with a path prefix for nodes behind a reverse
+/// proxy. Trailing slashes are removed.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::rest::parse_host;
+///
+/// assert_eq!(parse_host("127.0.0.1:2333")?, "http://127.0.0.1:2333");
+/// assert_eq!(
+///     parse_host("HTTPS://lavalink.example.com/node/")?,
+///     "https://lavalink.example.com/node",
+/// );
+/// assert!(parse_host("ws://127.0.0.1:2333").is_err());
+/// # Ok::<(), lavalink::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidHost`] if the host is empty, has a scheme other
+/// than HTTP or HTTPS, has an invalid port, or has a query or fragment.
+///
+/// [`Error::InvalidHost`]: ../enum.Error.html#variant.InvalidHost
+#[inline]
+pub fn parse_host(host: impl AsRef<str>) -> Result<String> {
+    _parse_host(host.as_ref())
+}
+
+fn _parse_host(host: &str) -> Result<String> {
+    let invalid = || Error::InvalidHost(host.to_owned());
+    let trimmed = host.trim();
+
+    let (scheme, rest) = match trimmed.find("://") {
+        Some(idx) => {
+            let scheme = trimmed[..idx].to_ascii_lowercase();
+
+            if scheme != "http" && scheme != "https" {
+                return Err(invalid());
+            }
+
+            (scheme, &trimmed[idx + 3..])
+        },
+        None => ("http".to_owned(), trimmed),
+    };
+
+    if rest.contains(|c: char| c == '?' || c == '#' || c.is_whitespace()) {
+        return Err(invalid());
+    }
+
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+
+    // the port is after the last colon, unless that's inside an IPv6 address
+    let host_end = authority.rfind(']').unwrap_or(0);
+    let (name, port) = match authority[host_end..].rfind(':') {
+        Some(idx) => {
+            let idx = host_end + idx;
+
+            (&authority[..idx], Some(&authority[idx + 1..]))
+        },
+        None => (authority, None),
+    };
+
+    if name.is_empty() || name.contains('@') {
+        return Err(invalid());
+    }
+
+    if let Some(port) = port {
+        if port.parse::<u16>().is_err() {
+            return Err(invalid());
+        }
+    }
+
+    Ok(format!("{}://{}{}", scheme, authority, path.trim_end_matches('/')))
+}
+
+/// The default longest identifier that the owned REST clients load, in
+/// bytes.
+pub const DEFAULT_MAX_IDENTIFIER_LENGTH: usize = 2048;
+
+/// The reason that an identifier was rejected before loading it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum IdentifierError {
+    /// The identifier contains a control character, such as a newline.
+    ControlCharacter {
+        /// The byte index of the character.
+        index: usize,
+    },
+    /// The identifier is longer than the client's limit.
+    TooLong {
+        /// The length of the identifier, in bytes.
+        length: usize,
+        /// The longest identifier allowed, in bytes.
+        max: usize,
+    },
+}
+
+impl Display for IdentifierError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            IdentifierError::ControlCharacter { index } => write!(
+                f,
+                "Identifier contains a control character at byte {}",
+                index,
+            ),
+            IdentifierError::TooLong { length, max } => write!(
+                f,
+                "Identifier is {} bytes long, more than the maximum of {}",
+                length,
+                max,
+            ),
+        }
+    }
+}
+
+impl StdError for IdentifierError {}
+
+/// A node responded to a request with an unsuccessful HTTP status, such as
+/// `401 Unauthorized` for a wrong password.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct StatusError {
+    /// The body of the response, which usually describes the error.
+    pub body: String,
+    /// The HTTP status code, such as `404`.
+    pub status: u16,
+}
+
+impl Display for StatusError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "Node responded with status {}", self.status)?;
+
+        if self.body.is_empty() {
+            Ok(())
+        } else {
+            write!(f, ": {}", self.body)
+        }
+    }
+}
+
+impl StdError for StatusError {}
+
+/// Returns the body of a response, or a [`StatusError`] if its status isn't
+/// successful.
+///
+/// [`StatusError`]: struct.StatusError.html
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn check_status(status: u16, body: Vec<u8>) -> Result<Vec<u8>> {
+    if (200..300).contains(&status) {
+        return Ok(body);
+    }
+
+    Err(Error::Status(StatusError {
+        body: String::from_utf8_lossy(&body).into_owned(),
+        status,
+    }))
+}
+
+/// Checks that an identifier is safe to load, and not longer than a limit.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn check_identifier(
+    identifier: &str,
+    max_length: Option<usize>,
+) -> Result<()> {
+    if let Some(max) = max_length {
+        if identifier.len() > max {
+            return Err(Error::InvalidIdentifier(IdentifierError::TooLong {
+                length: identifier.len(),
+                max,
+            }));
+        }
+    }
+
+    match identifier.char_indices().find(|(_, c)| c.is_control()) {
+        Some((index, _)) => Err(Error::InvalidIdentifier(
+            IdentifierError::ControlCharacter { index },
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The headers that are set by the library on every request, which can't be
+/// given as extra headers.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+const RESERVED_HEADERS: &[&str] = &[
+    "authorization",
+    "content-length",
+    "content-type",
+    "host",
+    "x-request-id",
+];
+
+/// Checks that none of the names of extra headers are reserved.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn check_extra_headers<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    for name in names {
+        if RESERVED_HEADERS.iter().any(|r| name.eq_ignore_ascii_case(r)) {
+            return Err(Error::ReservedHeader(name.to_owned()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Percent-encodes a value for use as a query parameter's key or value,
+/// leaving only unreserved characters as they are.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn encode_query_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char);
+            },
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+/// Creates the URI path for loading tracks matching an identifier, with
+/// additional query parameters appended in order.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn load_tracks_uri(
+    identifier: &str,
+    params: &[(&str, &str)],
+) -> String {
+    let mut uri = format!(
+        "/loadtracks?identifier={}",
+        encode_query_component(identifier),
+    );
+
+    for (key, value) in params {
+        uri.push('&');
+        uri.push_str(&encode_query_component(key));
+        uri.push('=');
+        uri.push_str(&encode_query_component(value));
+    }
+
+    uri
+}
+
+/// The length above which a track is decoded through `/decodetracks` instead
+/// of being sent in the query of a `/decodetrack` request, to stay below the
+/// URI length limits of nodes and proxies.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) const DECODE_TRACK_QUERY_LIMIT: usize = 2048;
+
+/// Creates the body of a `/decodetracks` request decoding a single track.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn decode_single_track_body(track: &str) -> Result<Vec<u8>> {
+    serde_json::to_vec(&[track]).map_err(From::from)
+}
+
+/// Takes the only track out of the response to a `/decodetracks` request
+/// decoding a single track.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn take_single_track(tracks: Vec<LoadedTrack>) -> Result<LoadedTrack> {
+    tracks.into_iter().next().ok_or_else(|| {
+        Error::Json(serde::de::Error::invalid_length(0, &"one decoded track"))
+    })
+}
+
+/// The response of a node when decoding a single track.
+///
+/// Lavalink v3 nodes respond with only the track's information, while v4
+/// nodes respond with a full track object.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum DecodeTrackResponse {
+    Track(LoadedTrack),
+    Info(LoadedTrackInfo),
+}
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+impl DecodeTrackResponse {
+    /// Converts the response into a track, using the given base64 encoded
+    /// track if the node did not echo it back.
+    pub(crate) fn into_loaded_track(self, track: String) -> LoadedTrack {
+        match self {
+            DecodeTrackResponse::Track(loaded) => loaded,
+            DecodeTrackResponse::Info(info) => LoadedTrack {
+                info,
+                plugin_info: Value::Null,
+                track,
+            },
+        }
+    }
 }
 
 /// Information about a playlist, if any.
 #[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename = "camelCase")]
+#[serde(rename_all = "camelCase")]
 pub struct PlaylistInfo {
     /// The name of the playlist.
     pub name: Option<String>,
     /// The item that was selected.
+    ///
+    /// Nodes send `-1` when no item was selected, which is `None`.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_selected_track",
+        skip_serializing_if = "Option::is_none",
+    )]
     pub selected_track: Option<u64>,
 }
+
+fn deserialize_selected_track<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> StdResult<Option<u64>, D::Error> {
+    let selected = Option::<i64>::deserialize(deserializer)?;
+
+    Ok(selected.and_then(|index| u64::try_from(index).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::*;
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    static DECODED_V3: &str = r#"{
+  "title": "Never Gonna Give You Up",
+  "author": "RickAstleyVEVO",
+  "length": 212000,
+  "identifier": "dQw4w9WgXcQ",
+  "uri": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+  "isStream": false,
+  "isSeekable": true,
+  "position": 0
+}"#;
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    static DECODED_V4: &str = r#"{
+  "encoded": "QAAAjQIAJVJpY2sgQXN0bGV5",
+  "info": {
+    "title": "Never Gonna Give You Up",
+    "author": "RickAstleyVEVO",
+    "length": 212000,
+    "identifier": "dQw4w9WgXcQ",
+    "uri": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+    "isStream": false,
+    "isSeekable": true,
+    "position": 0,
+    "artworkUrl": "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg",
+    "isrc": null,
+    "sourceName": "youtube"
+  },
+  "pluginInfo": {
+    "albumName": "Whenever You Need Somebody"
+  }
+}"#;
+
+    #[test]
+    fn test_node_version() {
+        let version = "4.0.0-beta.1".parse::<NodeVersion>().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (4, 0, 0));
+        assert_eq!(version.pre_release.as_ref().unwrap(), "beta.1");
+        assert_eq!(version.capabilities(), NodeCapabilities {
+            equalizer_op: false,
+            filters: true,
+            rest_players: true,
+        });
+
+        let version = "3.3".parse::<NodeVersion>().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (3, 3, 0));
+        assert!(!version.capabilities().filters);
+
+        let version = "4.0.0-rc.1+abc".parse::<NodeVersion>().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (4, 0, 0));
+        assert_eq!(version.pre_release.as_ref().unwrap(), "rc.1");
+        assert_eq!(version.semver, "4.0.0-rc.1+abc");
+
+        let version = "4.0.0+abc".parse::<NodeVersion>().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (4, 0, 0));
+        assert!(version.pre_release.is_none());
+
+        let version = "4.0.0-SNAPSHOT".parse::<NodeVersion>().unwrap();
+        assert_eq!(version.major, 4);
+        assert_eq!(version.pre_release.as_ref().unwrap(), "SNAPSHOT");
+
+        let version = "3ee7c8b-SNAPSHOT".parse::<NodeVersion>().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (0, 0, 0));
+        assert_eq!(version.pre_release.as_ref().unwrap(), "3ee7c8b-SNAPSHOT");
+
+        assert!("".parse::<NodeVersion>().is_err());
+        assert!("v3.7".parse::<NodeVersion>().is_err());
+        assert!("3ee7c8b".parse::<NodeVersion>().is_err());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_player_update_request() {
+        let mut update = PlayerUpdateRequest::new().paused(true).volume(50);
+        update.merge(PlayerUpdateRequest::new().volume(80).no_replace(true));
+
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"paused":true,"volume":80}"#,
+        );
+        assert_eq!(
+            update.uri("abc", "1"),
+            "/v4/sessions/abc/players/1?noReplace=true",
+        );
+        assert!(PlayerUpdateRequest::new().is_empty());
+        assert!(!update.is_empty());
+
+        // a later request can turn noReplace off again
+        update.merge(PlayerUpdateRequest::new().no_replace(false));
+        assert_eq!(
+            update.uri("abc", "1"),
+            "/v4/sessions/abc/players/1?noReplace=false",
+        );
+        update.merge(PlayerUpdateRequest::new());
+        assert_eq!(update.no_replace, Some(false));
+    }
+
+    #[test]
+    fn test_player_update_user_data() {
+        let data = || serde_json::json!({ "requester": "1" });
+
+        let update = PlayerUpdateRequest::new()
+            .encoded_track("QAAA")
+            .user_data(data());
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"track":{"encoded":"QAAA","userData":{"requester":"1"}}}"#,
+        );
+        assert_eq!(
+            serde_json::to_string(&PlayerUpdateRequest::new().encoded_track("QAAA")).unwrap(),
+            r#"{"track":{"encoded":"QAAA"}}"#,
+        );
+
+        // a new track doesn't keep the data of the previous one
+        let mut merged = update.clone();
+        merged.merge(PlayerUpdateRequest::new().encoded_track("QBBB"));
+        assert_eq!(
+            serde_json::to_string(&merged).unwrap(),
+            r#"{"track":{"encoded":"QBBB"}}"#,
+        );
+
+        // data on its own is attached to the pending track
+        let mut merged = PlayerUpdateRequest::new().encoded_track("QCCC");
+        merged.merge(PlayerUpdateRequest::new().user_data(data()));
+        assert_eq!(
+            serde_json::to_string(&merged).unwrap(),
+            r#"{"track":{"encoded":"QCCC","userData":{"requester":"1"}}}"#,
+        );
+
+        let mut merged = PlayerUpdateRequest::new().paused(true);
+        merged.merge(update);
+        assert_eq!(merged.track.unwrap().encoded.unwrap(), "QAAA");
+    }
+
+    #[test]
+    fn test_parse_host() {
+        use super::parse_host;
+
+        assert_eq!(parse_host(" localhost:2333 ").unwrap(), "http://localhost:2333");
+        assert_eq!(parse_host("http://[::1]:2333/").unwrap(), "http://[::1]:2333");
+        assert_eq!(parse_host("https://example.com").unwrap(), "https://example.com");
+
+        for host in &["", "http://", ":2333", "localhost:99999", "localhost:port",
+                      "ftp://localhost", "localhost:2333/?a=b", "user@localhost"] {
+            match parse_host(host) {
+                Err(Error::InvalidHost(ref invalid)) if invalid == host => {},
+                other => panic!("{:?} parsed as {:?}", host, other),
+            }
+        }
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_fallback_plan() {
+        use super::{FallbackPlan, Source};
+
+        let sources = || vec![Source::YouTube, Source::SoundCloud];
+
+        let plan = FallbackPlan::new("ytsearch:never gonna");
+        assert_eq!(plan.primary, "ytsearch:never gonna");
+        assert_eq!(plan.fallbacks(sources()), vec![
+            (Source::SoundCloud, "scsearch:never gonna".to_owned()),
+        ]);
+
+        let plan = FallbackPlan::new(
+            "QAAAIwIAAXQAAWEAAAAAAAAD6AACaWQAAAAEaHR0cAAAAAAAAAAA",
+        );
+        assert_eq!(plan.fallbacks(sources())[0].1, "ytsearch:a t");
+
+        let plan = FallbackPlan::new("https://example.com/track.mp3");
+        assert!(plan.fallbacks(sources()).is_empty());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_check_identifier() {
+        use super::{check_identifier, IdentifierError};
+
+        assert!(check_identifier("ytsearch:never gonna", Some(20)).is_ok());
+        assert!(check_identifier(&"a".repeat(10_000), None).is_ok());
+
+        match check_identifier("ytsearch:never gonna", Some(19)) {
+            Err(Error::InvalidIdentifier(IdentifierError::TooLong {
+                length: 20,
+                max: 19,
+            })) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match check_identifier("ytsearch:a\nb", None) {
+            Err(Error::InvalidIdentifier(
+                IdentifierError::ControlCharacter { index: 10 },
+            )) => {},
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_load_tracks_uri() {
+        assert_eq!(
+            super::load_tracks_uri("ytsearch:foo bar", &[]),
+            "/loadtracks?identifier=ytsearch%3Afoo%20bar",
+        );
+        // reserved characters can't end the identifier or add parameters
+        assert_eq!(
+            super::load_tracks_uri("ytsearch:simon & garfunkel+1=#2", &[]),
+            "/loadtracks?identifier=ytsearch%3Asimon%20%26%20garfunkel%2B1%3D%232",
+        );
+        assert_eq!(
+            super::load_tracks_uri("ytsearch:foo", &[
+                ("region", "us-east"),
+                ("a&b", "c=d+e"),
+            ]),
+            "/loadtracks?identifier=ytsearch%3Afoo&region=us-east&a%26b=c%3Dd%2Be",
+        );
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_decode_single_track() {
+        assert_eq!(decode_single_track_body("foo").unwrap(), br#"["foo"]"#);
+
+        let track = serde_json::from_str::<LoadedTrack>(DECODED_V4).unwrap();
+        assert!(take_single_track(vec![track]).is_ok());
+        assert!(take_single_track(vec![]).is_err());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_decode_track_response_v3() {
+        let response = serde_json::from_str::<DecodeTrackResponse>(DECODED_V3)
+            .unwrap();
+        let track = response.into_loaded_track("foo".to_owned());
+        assert_eq!(track.track, "foo");
+        assert_eq!(track.info.identifier, "dQw4w9WgXcQ");
+        assert!(track.plugin_info.is_null());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_decode_track_response_v4() {
+        let response = serde_json::from_str::<DecodeTrackResponse>(DECODED_V4)
+            .unwrap();
+        let track = response.into_loaded_track("foo".to_owned());
+        assert_eq!(track.track, "QAAAjQIAJVJpY2sgQXN0bGV5");
+        assert_eq!(track.info.identifier, "dQw4w9WgXcQ");
+        assert_eq!(
+            track.info.artwork_url.as_ref().unwrap(),
+            "https://i.ytimg.com/vi/dQw4w9WgXcQ/maxresdefault.jpg",
+        );
+        assert!(track.info.isrc.is_none());
+        assert_eq!(
+            track.plugin_info["albumName"],
+            "Whenever You Need Somebody",
+        );
+    }
+
+    #[test]
+    fn test_playlist_info_selected_track() {
+        let info = serde_json::from_str::<PlaylistInfo>(
+            r#"{"name":"foo","selectedTrack":-1}"#,
+        ).unwrap();
+        assert_eq!(info.name.as_ref().unwrap(), "foo");
+        assert!(info.selected_track.is_none());
+
+        let info = serde_json::from_str::<PlaylistInfo>(
+            r#"{"name":"foo","selectedTrack":2}"#,
+        ).unwrap();
+        assert_eq!(info.selected_track, Some(2));
+
+        let info = serde_json::from_str::<PlaylistInfo>("{}").unwrap();
+        assert!(info.name.is_none());
+        assert!(info.selected_track.is_none());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_check_status() {
+        use crate::ErrorKind;
+
+        assert_eq!(check_status(200, b"{}".to_vec()).unwrap(), b"{}");
+        assert!(check_status(204, Vec::new()).unwrap().is_empty());
+
+        let error = check_status(401, b"Unauthorized".to_vec()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Configuration);
+        assert_eq!(
+            error.to_string(),
+            "Node responded with status 401: Unauthorized",
+        );
+
+        let error = check_status(503, Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Transport);
+        assert_eq!(error.to_string(), "Node responded with status 503");
+
+        match check_status(404, b"Not Found".to_vec()) {
+            Err(Error::Status(ref inner)) => {
+                assert_eq!(inner.status, 404);
+                assert_eq!(inner.body, "Not Found");
+                assert_eq!(Error::Status(inner.clone()).kind(), ErrorKind::InvalidInput);
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_check_extra_headers() {
+        assert!(check_extra_headers(vec!["x-proxy-token"]).is_ok());
+
+        // set on every request to tell the requests apart
+        match check_extra_headers(vec!["X-Request-Id"]) {
+            Err(Error::ReservedHeader(ref name)) => assert_eq!(name, "X-Request-Id"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match check_extra_headers(vec!["x-proxy-token", "Authorization"]) {
+            Err(Error::ReservedHeader(ref name)) => assert_eq!(name, "Authorization"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
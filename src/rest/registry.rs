@@ -0,0 +1,184 @@
+//! A registry of loaded tracks by short identifiers, for referencing tracks
+//! where the full track blob doesn't fit, such as in the custom IDs of
+//! Discord's message components, which are limited to 100 characters.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use lavalink::rest::registry::TrackRegistry;
+//! use lavalink::rest::LoadedTrack;
+//!
+//! # let track = serde_json::from_value::<LoadedTrack>(serde_json::json!({
+//! #     "track": "QAAAjQIAJVJpY2sgQXN0bGV5IC0gTmV2ZXIgR29ubmEgR2l2ZSBZb3UgVXAADlJpY2tBc3RsZXlWRVZPAAAAAAADPCAAC2RRdzR3OVdnWGNRAAEAK2h0dHBzOi8vd3d3LnlvdXR1YmUuY29tL3dhdGNoP3Y9ZFF3NHc5V2dYY1EAB3lvdXR1YmUAAAAAAAAAAA==",
+//! #     "info": {
+//! #         "identifier": "dQw4w9WgXcQ",
+//! #         "isSeekable": true,
+//! #         "author": "RickAstleyVEVO",
+//! #         "length": 212000,
+//! #         "isStream": false,
+//! #         "position": 0,
+//! #         "title": "Rick Astley - Never Gonna Give You Up",
+//! #         "uri": "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+//! #     }
+//! # }))?;
+//! let mut registry = TrackRegistry::new();
+//! let id = registry.insert(track);
+//! let custom_id = format!("requeue:{}", id);
+//!
+//! // later, when the button is pressed
+//! let id = custom_id.trim_start_matches("requeue:");
+//!
+//! if let Some(track) = registry.get(id) {
+//!     println!("Requeueing {}", track.info.title);
+//! }
+//! # assert!(registry.get(id).is_some());
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use super::LoadedTrack;
+
+/// Hashes bytes with 64-bit FNV-1a, which unlike the standard library's
+/// hasher is guaranteed to be the same everywhere.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// A collection of tracks by their [`short_hash`].
+///
+/// If a different track with the same hash is already registered, the track
+/// is registered with a suffix of `-1`, `-2` and so on instead, so every
+/// identifier refers to exactly one track.
+///
+/// [`short_hash`]: ../struct.LoadedTrack.html#method.short_hash
+#[derive(Clone, Debug, Default)]
+pub struct TrackRegistry {
+    /// The identifiers of the registered tracks, by their blobs.
+    ids: HashMap<String, String>,
+    tracks: HashMap<String, LoadedTrack>,
+}
+
+impl TrackRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a track, returning the identifier to look it up by.
+    ///
+    /// The identifier is the track's 16 character hash, unless it collided
+    /// with another track's. Registering a track that is already registered
+    /// returns its existing identifier.
+    pub fn insert(&mut self, track: LoadedTrack) -> String {
+        // looked up by blob, as the track may have a suffix even if the
+        // identifiers before it are free again
+        if let Some(id) = self.ids.get(&track.track) {
+            return id.clone();
+        }
+
+        let hash = track.short_hash();
+        let mut id = hash.clone();
+        let mut suffix = 0u32;
+
+        while self.tracks.contains_key(&id) {
+            suffix += 1;
+            id = format!("{}-{}", hash, suffix);
+        }
+
+        self.ids.insert(track.track.clone(), id.clone());
+        self.tracks.insert(id.clone(), track);
+
+        id
+    }
+
+    /// Returns the track registered with an identifier.
+    pub fn get(&self, id: impl AsRef<str>) -> Option<&LoadedTrack> {
+        self.tracks.get(id.as_ref())
+    }
+
+    /// Removes the track registered with an identifier, returning it.
+    ///
+    /// The identifier may be reused for a different track afterwards.
+    pub fn remove(&mut self, id: impl AsRef<str>) -> Option<LoadedTrack> {
+        let track = self.tracks.remove(id.as_ref())?;
+        self.ids.remove(&track.track);
+
+        Some(track)
+    }
+
+    /// Removes all tracks.
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.tracks.clear();
+    }
+
+    /// Returns the number of registered tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Returns whether no tracks are registered.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest::LoadedTrack;
+    use serde_json::{self, json};
+    use super::{fnv1a, TrackRegistry};
+
+    fn track(blob: &str) -> LoadedTrack {
+        serde_json::from_value(json!({
+            "track": blob,
+            "info": {
+                "title": "Never Gonna Give You Up",
+                "author": "RickAstleyVEVO",
+                "length": 212000,
+                "identifier": "dQw4w9WgXcQ",
+                "uri": "https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+                "isStream": false,
+                "isSeekable": true,
+                "position": 0
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_short_hash() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(track("a").short_hash(), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = TrackRegistry::new();
+        let id = registry.insert(track("QAAA"));
+        assert_eq!(id, track("QAAA").short_hash());
+        assert_eq!(registry.insert(track("QAAA")), id);
+        assert_eq!(registry.len(), 1);
+
+        // simulate a different track with the same hash
+        let other = registry.remove(&id).unwrap();
+        registry.ids.insert("QBBB".to_owned(), id.clone());
+        registry.tracks.insert(id.clone(), track("QBBB"));
+        let collided = registry.insert(other);
+        assert_eq!(collided, format!("{}-1", id));
+        assert_eq!(registry.get(&collided).unwrap().track, "QAAA");
+        assert_eq!(registry.get(&id).unwrap().track, "QBBB");
+
+        assert!(registry.remove(&id).is_some());
+        assert!(registry.get(&id).is_none());
+        assert_eq!(registry.len(), 1);
+
+        // the track keeps its suffix after the identifier before it is freed
+        assert_eq!(registry.insert(track("QAAA")), collided);
+        assert_eq!(registry.len(), 1);
+    }
+}
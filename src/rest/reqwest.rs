@@ -1,44 +1,314 @@
 //! A trait implementation for Reqwest's Client and an owned client for working
 //! with the Lavalink REST API.
 
-use crate::Result;
-use percent_encoding::{self, DEFAULT_ENCODE_SET};
+use crate::credentials::{CredentialProvider, ProviderSlot};
+use crate::{Error, Result, Secret};
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
-use reqwest::{Body, Client as ReqwestClient, Method, Request, RequestBuilder};
+use reqwest::{Body, Client as ReqwestClient, Method, Request, RequestBuilder, Response};
 use serde_json;
 use std::io::Read;
-use super::{Load, LoadedTrack};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::time::Duration;
+use super::enrich::{Enricher, Enrichers};
+use super::metrics::{self, RestMetrics};
+use super::{
+    decode_single_track_body,
+    take_single_track,
+    DecodeTrackResponse,
+    DECODE_TRACK_QUERY_LIMIT,
+    DEFAULT_MAX_IDENTIFIER_LENGTH,
+    FallbackLoad,
+    FallbackPlan,
+    Load,
+    LoadedTrack,
+    NodeInfo,
+    NodeVersion,
+    Player,
+    PlayerUpdateRequest,
+    Session,
+    Source,
+};
+
+use super::route_planner::{self, RoutePlannerStatus};
+
+#[cfg(feature = "plugin-lavalyrics")]
+use super::lavalyrics::{self, Lyrics};
+#[cfg(feature = "plugin-lavasearch")]
+use super::lavasearch::{self, SearchResult, SearchType};
 
 /// An HTTP client used to communicate with a LavaLink node.
 #[derive(Debug)]
 pub struct RestClient {
     client: ReqwestClient,
+    credentials: ProviderSlot,
+    enrichers: Enrichers<dyn Enricher>,
     host: String,
-    password: Vec<u8>,
+    max_identifier_length: Option<usize>,
+    metrics: Arc<RestMetrics>,
+    password: RwLock<Secret>,
+    session_id: Option<String>,
 }
 
 impl RestClient {
     /// Creates a new reqwest Client wrapper used to communicate with a LavaLink
     /// node.
     ///
+    /// The host is parsed with [`parse_host`], so it can be given either as
+    /// `host:port` or as a URL.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
     /// use lavalink::rest::reqwest::RestClient;
     ///
-    /// let client = RestClient::new("127.0.0.1:2333", "test_password");
+    /// let client = RestClient::new("127.0.0.1:2333", "test_password")?;
+    /// #     Ok(())
+    /// # }
     /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidHost`] if the host could not be parsed.
+    ///
+    /// [`Error::InvalidHost`]: ../../enum.Error.html#variant.InvalidHost
+    /// [`parse_host`]: ../fn.parse_host.html
     #[inline]
-    pub fn new(host: impl Into<String>, password: impl Into<Vec<u8>>) -> Self {
-        Self::_new(host.into(), password.into())
+    pub fn new(
+        host: impl AsRef<str>,
+        password: impl Into<Vec<u8>>,
+    ) -> Result<Self> {
+        Self::_new(host.as_ref(), password.into())
     }
 
-    fn _new(host: String, password: Vec<u8>) -> Self {
-        Self {
+    fn _new(host: &str, password: Vec<u8>) -> Result<Self> {
+        Ok(Self {
             client: ReqwestClient::new(),
-            host,
-            password,
+            credentials: ProviderSlot::default(),
+            enrichers: Enrichers::default(),
+            host: super::parse_host(host)?,
+            max_identifier_length: Some(DEFAULT_MAX_IDENTIFIER_LENGTH),
+            metrics: Arc::new(RestMetrics::default()),
+            password: RwLock::new(Secret::new(password)),
+            session_id: None,
+        })
+    }
+
+    /// Returns the latency statistics of the requests made by the client.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::reqwest::RestClient;
+    ///
+    /// let client = RestClient::new("http://127.0.0.1:2333", "test_password")?;
+    /// client.load_tracks("ytsearch:never gonna give you up")?;
+    ///
+    /// if let Some(stats) = client.metrics().endpoint("loadtracks") {
+    ///     println!("average latency: {:?}", stats.average());
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn metrics(&self) -> &Arc<RestMetrics> {
+        &self.metrics
+    }
+
+    /// Sends a request with a new request ID, and records its latency and
+    /// result in the client's metrics.
+    fn timed<T>(
+        &self,
+        endpoint: &'static str,
+        request: impl FnOnce(&WithRequestId<'_>) -> Result<T>,
+    ) -> Result<T> {
+        let requester = WithRequestId {
+            client: &self.client,
+            request_id: metrics::next_request_id(),
+        };
+        let timer = self.metrics.start(endpoint, requester.request_id);
+        let result = request(&requester);
+        self.metrics.finish(timer, result.is_ok());
+
+        result
+    }
+
+    /// Returns the ID of the node session used by Lavalink v4 endpoints, if
+    /// one was set.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Sets the ID of the node session used by Lavalink v4 endpoints.
+    ///
+    /// This is the session ID received in the node's [`Ready`] message.
+    ///
+    /// [`Ready`]: ../../model/struct.Ready.html
+    pub fn set_session_id(&mut self, session_id: impl Into<String>) {
+        self.session_id = Some(session_id.into());
+    }
+
+    /// Replaces the password used to authenticate with the node, such as
+    /// after it was rotated.
+    ///
+    /// Requests started after this call use the new password, while requests
+    /// that were already sent keep the old one.
+    pub fn set_password(&self, password: impl Into<Vec<u8>>) {
+        self.replace_password(Secret::new(password));
+    }
+
+    /// Sets the provider that the password used to authenticate with the
+    /// node is read from, and reads it.
+    ///
+    /// See the [`credentials`] module for an example.
+    ///
+    /// # Errors
+    ///
+    /// Returns the provider's error if the password couldn't be read, in
+    /// which case the provider and password are left unchanged.
+    ///
+    /// [`credentials`]: ../../credentials/index.html
+    pub fn set_credential_provider(
+        &mut self,
+        provider: impl CredentialProvider + 'static,
+    ) -> Result<()> {
+        self.replace_password(provider.password()?);
+        self.credentials.set(Box::new(provider));
+
+        Ok(())
+    }
+
+    /// Reads the password again from the client's credential provider, such
+    /// as after it was rotated.
+    ///
+    /// This does nothing if no provider was set.
+    ///
+    /// # Errors
+    ///
+    /// Returns the provider's error if the password couldn't be read, in
+    /// which case the current password is kept.
+    pub fn refresh_credentials(&self) -> Result<()> {
+        if let Some(password) = self.credentials.password() {
+            self.replace_password(password?);
         }
+
+        Ok(())
+    }
+
+    fn replace_password(&self, password: Secret) {
+        *self.password.write().unwrap_or_else(|poisoned| {
+            poisoned.into_inner()
+        }) = password;
+    }
+
+    /// Adds a step that changes every track loaded by the client's
+    /// `load_tracks` methods, run after the enrichers that were already
+    /// added.
+    ///
+    /// See the [`enrich`] module for an example.
+    ///
+    /// [`enrich`]: ../enrich/index.html
+    pub fn add_enricher(&mut self, enricher: impl Enricher + 'static) {
+        self.enrichers.push(Arc::new(enricher));
+    }
+
+    /// Sets the longest identifier that the client's `load_tracks` methods
+    /// load, in bytes, or `None` to allow identifiers of any length.
+    ///
+    /// Identifiers are checked before sending a request, so that garbage
+    /// input isn't sent to the node. This defaults to
+    /// [`DEFAULT_MAX_IDENTIFIER_LENGTH`], which can be raised for source
+    /// plugins that accept long `data:` URIs. Identifiers with control
+    /// characters are always rejected.
+    ///
+    /// [`DEFAULT_MAX_IDENTIFIER_LENGTH`]: ../constant.DEFAULT_MAX_IDENTIFIER_LENGTH.html
+    pub fn set_max_identifier_length(&mut self, max: Option<usize>) {
+        self.max_identifier_length = max;
+    }
+
+    /// Sets headers to send with every request in addition to the ones the
+    /// library sets, such as for an authenticating proxy in front of the
+    /// node.
+    ///
+    /// This replaces any extra headers that were set before.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::reqwest::RestClient;
+    /// use reqwest::header::{HeaderMap, HeaderValue};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-Proxy-Token", HeaderValue::from_static("secret"));
+    ///
+    /// let mut client = RestClient::new("127.0.0.1:2333", "test_password")?;
+    /// client.set_extra_headers(headers)?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ReservedHeader`] if a header is one that the library
+    /// sets itself, such as `Authorization`.
+    ///
+    /// Returns [`Error::Reqwest`] if the underlying client could not be
+    /// rebuilt with the headers.
+    ///
+    /// [`Error::ReservedHeader`]: ../../enum.Error.html#variant.ReservedHeader
+    /// [`Error::Reqwest`]: ../../enum.Error.html#variant.Reqwest
+    pub fn set_extra_headers(&mut self, headers: HeaderMap) -> Result<()> {
+        super::check_extra_headers(headers.keys().map(|name| name.as_str()))?;
+
+        // reqwest only applies default headers when building a client
+        self.client = ReqwestClient::builder().default_headers(headers).build()?;
+
+        Ok(())
+    }
+
+    fn password(&self) -> RwLockReadGuard<'_, Secret> {
+        // the password is replaced in a single assignment, so a panic while
+        // holding the lock can't leave it partially written
+        self.password.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Configures whether the node session can be resumed, and for how long
+    /// after the connection to the node closes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::reqwest::RestClient;
+    /// use std::time::Duration;
+    ///
+    /// let mut client = RestClient::new("http://127.0.0.1:2333", "test_password")?;
+    /// client.set_session_id("la3kfsdf5eafe848");
+    /// client.update_session(true, Duration::from_secs(60))?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingSessionId`] if no session ID was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    pub fn update_session(
+        &self,
+        resuming: bool,
+        timeout: Duration,
+    ) -> Result<Session> {
+        let session_id = self.session_id().ok_or(Error::MissingSessionId)?;
+
+        self.timed("sessions", |client| client.update_session(
+            &self.host,
+            &*self.password(),
+            session_id,
+            resuming,
+            timeout,
+        ))
     }
 
     /// Loads tracks matching an identifier via a given node.
@@ -49,7 +319,108 @@ impl RestClient {
     }
 
     fn _load_tracks(&self, identifier: &str) -> Result<Load> {
-        self.client.load_tracks(&self.host, &self.password, identifier)
+        self._load_tracks_with(identifier, &[])
+    }
+
+    /// Loads tracks matching an identifier via a given node, appending
+    /// additional query parameters to the request.
+    ///
+    /// This is useful for source plugins that accept extra parameters, such
+    /// as region hints. The keys and values are percent-encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::reqwest::RestClient;
+    ///
+    /// let client = RestClient::new("http://127.0.0.1:2333", "test_password")?;
+    /// let load = client.load_tracks_with("ytsearch:never gonna", &[
+    ///     ("region", "us-east"),
+    /// ])?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn load_tracks_with(
+        &self,
+        identifier: impl AsRef<str>,
+        params: &[(&str, &str)],
+    ) -> Result<Load> {
+        self._load_tracks_with(identifier.as_ref(), params)
+    }
+
+    fn _load_tracks_with(
+        &self,
+        identifier: &str,
+        params: &[(&str, &str)],
+    ) -> Result<Load> {
+        super::check_identifier(identifier, self.max_identifier_length)?;
+
+        let mut load = self.timed("loadtracks", |client| {
+            client.load_tracks_with(
+                &self.host,
+                &*self.password(),
+                identifier,
+                params,
+            )
+        })?;
+        self.enrichers.apply(&mut load);
+
+        Ok(load)
+    }
+
+    /// Loads tracks matching an identifier, searching other sources for the
+    /// track if none were found.
+    ///
+    /// If the identifier is an encoded track, its URL is loaded, and its
+    /// author and title are searched for on each source in order until a
+    /// load finds tracks. If the identifier is a search, its query is
+    /// searched for instead. Other identifiers, such as URLs, have nothing to
+    /// search for, so they aren't retried.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::reqwest::RestClient;
+    /// use lavalink::rest::Source;
+    ///
+    /// let client = RestClient::new("http://127.0.0.1:2333", "test_password")?;
+    /// let result = client.load_with_fallback("ytsearch:never gonna", vec![
+    ///     Source::YouTubeMusic,
+    ///     Source::SoundCloud,
+    /// ])?;
+    ///
+    /// if let Some(source) = result.source {
+    ///     println!("Found {} tracks on {:?}", result.load.tracks.len(), source);
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn load_with_fallback(
+        &self,
+        identifier: impl AsRef<str>,
+        sources: impl IntoIterator<Item = Source>,
+    ) -> Result<FallbackLoad> {
+        let plan = FallbackPlan::new(identifier.as_ref());
+        let mut result = FallbackLoad {
+            load: self._load_tracks(&plan.primary)?,
+            source: None,
+        };
+
+        for (source, identifier) in plan.fallbacks(sources) {
+            if result.load.found_tracks() {
+                break;
+            }
+
+            result = FallbackLoad {
+                load: self._load_tracks(&identifier)?,
+                source: Some(source),
+            };
+        }
+
+        Ok(result)
     }
 
     /// Decodes a track via a given node.
@@ -62,7 +433,9 @@ impl RestClient {
     }
 
     fn _decode_track(&self, track: String) -> Result<LoadedTrack> {
-        self.client.decode_track(&self.host, &self.password, track)
+        self.timed("decodetrack", |client| {
+            client.decode_track(&self.host, &*self.password(), track)
+        })
     }
 
     /// Decodes a vector of tracks via a given node.
@@ -75,7 +448,193 @@ impl RestClient {
     }
 
     fn _decode_tracks(&self, tracks: Vec<Vec<u8>>) -> Result<Vec<LoadedTrack>> {
-        self.client.decode_tracks(&self.host, &self.password, tracks)
+        self.timed("decodetracks", |client| {
+            client.decode_tracks(&self.host, &*self.password(), tracks)
+        })
+    }
+
+    /// Retrieves the version of the node.
+    pub fn version(&self) -> Result<NodeVersion> {
+        self.timed("version", |client| client.version(&self.host, &*self.password()))
+    }
+
+    /// Retrieves information about a Lavalink v4 node, such as its version,
+    /// plugins and enabled filters.
+    pub fn info(&self) -> Result<NodeInfo> {
+        self.timed("info", |client| client.info(&self.host, &*self.password()))
+    }
+
+    /// Retrieves the status of the node's route planner.
+    ///
+    /// Returns `None` if the node has no route planner configured.
+    pub fn route_planner_status(&self) -> Result<Option<RoutePlannerStatus>> {
+        self.timed("route_planner_status", |client| {
+            client.route_planner_status(&self.host, &*self.password())
+        })
+    }
+
+    /// Applies a set of changes to a guild's player on a Lavalink v4 node in
+    /// a single request, returning the updated player.
+    ///
+    /// This uses the session ID set with [`set_session_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::{reqwest::RestClient, PlayerUpdateRequest};
+    ///
+    /// let mut client = RestClient::new("http://127.0.0.1:2333", "test_password")?;
+    /// client.set_session_id("la3kfsdf5eafe848");
+    ///
+    /// let update = PlayerUpdateRequest::new().paused(false).volume(80);
+    /// let player = client.update_player("381880193251409931", &update)?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingSessionId`] if no session ID was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    /// [`set_session_id`]: #method.set_session_id
+    #[inline]
+    pub fn update_player(
+        &self,
+        guild_id: impl AsRef<str>,
+        update: &PlayerUpdateRequest,
+    ) -> Result<Player> {
+        let session_id = self.session_id().ok_or(Error::MissingSessionId)?;
+
+        self.timed("players", |client| client.update_player(
+            &self.host,
+            &*self.password(),
+            session_id,
+            guild_id,
+            update,
+        ))
+    }
+
+    /// Searches for a query via the node's LavaSearch plugin, returning
+    /// results of the given types.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # fn main() -> lavalink::Result<()> {
+    /// use lavalink::rest::{
+    ///     lavasearch::SearchType,
+    ///     reqwest::RestClient,
+    /// };
+    ///
+    /// let client = RestClient::new("http://127.0.0.1:2333", "test_password")?;
+    /// let result = client.search_advanced("spsearch:never gonna", vec![
+    ///     SearchType::Track,
+    ///     SearchType::Album,
+    /// ])?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "plugin-lavasearch")]
+    #[inline]
+    pub fn search_advanced(
+        &self,
+        query: impl AsRef<str>,
+        types: impl IntoIterator<Item = SearchType>,
+    ) -> Result<SearchResult> {
+        self._search_advanced(query.as_ref(), types.into_iter().collect())
+    }
+
+    #[cfg(feature = "plugin-lavasearch")]
+    fn _search_advanced(
+        &self,
+        query: &str,
+        types: Vec<SearchType>,
+    ) -> Result<SearchResult> {
+        self.timed("loadsearch", |client| {
+            client.search_advanced(&self.host, &*self.password(), query, types)
+        })
+    }
+
+    /// Retrieves the lyrics of an encoded track via the node's LavaLyrics
+    /// plugin, returning `None` if none were found.
+    ///
+    /// If `skip_track_source` is `true`, lyrics aren't retrieved from the
+    /// track's own source, such as YouTube's captions.
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[inline]
+    pub fn lyrics(
+        &self,
+        track: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Result<Option<Lyrics>> {
+        let track = track.as_ref();
+
+        self.timed("lyrics", |client| client.lyrics(
+            &self.host,
+            &*self.password(),
+            track,
+            skip_track_source,
+        ))
+    }
+
+    /// Retrieves the lyrics of a guild player's current track via the node's
+    /// LavaLyrics plugin, returning `None` if none were found.
+    ///
+    /// This uses the session ID set with [`set_session_id`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingSessionId`] if no session ID was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    /// [`set_session_id`]: #method.set_session_id
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[inline]
+    pub fn player_lyrics(
+        &self,
+        guild_id: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Result<Option<Lyrics>> {
+        let session_id = self.session_id().ok_or(Error::MissingSessionId)?;
+
+        self.timed("lyrics", |client| client.player_lyrics(
+            &self.host,
+            &*self.password(),
+            session_id,
+            guild_id,
+            skip_track_source,
+        ))
+    }
+
+    /// Subscribes or unsubscribes a guild's player to live lyrics events via
+    /// the node's LavaLyrics plugin.
+    ///
+    /// This uses the session ID set with [`set_session_id`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingSessionId`] if no session ID was set.
+    ///
+    /// [`Error::MissingSessionId`]: ../../enum.Error.html#variant.MissingSessionId
+    /// [`set_session_id`]: #method.set_session_id
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[inline]
+    pub fn set_lyrics_subscription(
+        &self,
+        guild_id: impl AsRef<str>,
+        subscribed: bool,
+    ) -> Result<()> {
+        let session_id = self.session_id().ok_or(Error::MissingSessionId)?;
+
+        self.timed("lyrics", |client| client.set_lyrics_subscription(
+            &self.host,
+            &*self.password(),
+            session_id,
+            guild_id,
+            subscribed,
+        ))
     }
 }
 
@@ -90,7 +649,20 @@ pub trait LavalinkRestRequester {
         identifier: impl AsRef<str>,
     ) -> Result<Load>;
 
+    /// Loads tracks matching an identifier via a given node, appending
+    /// additional query parameters to the request.
+    fn load_tracks_with(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+        params: &[(&str, &str)],
+    ) -> Result<Load>;
+
     /// Decodes a track via a given node.
+    ///
+    /// Very long tracks are sent in the body of a `/decodetracks` request
+    /// rather than in the URI.
     fn decode_track(
         &self,
         host: impl AsRef<str>,
@@ -105,9 +677,143 @@ pub trait LavalinkRestRequester {
         password: impl AsRef<[u8]>,
         tracks: impl IntoIterator<Item = impl Into<Vec<u8>>>,
     ) -> Result<Vec<LoadedTrack>>;
+
+    /// Retrieves the version of a given node.
+    fn version(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<NodeVersion>;
+
+    /// Retrieves information about a given Lavalink v4 node.
+    fn info(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<NodeInfo>;
+
+    /// Retrieves the status of a given node's route planner, returning
+    /// `None` if it has none.
+    fn route_planner_status(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<Option<RoutePlannerStatus>>;
+
+    /// Configures the resuming of a session on a given Lavalink v4 node.
+    fn update_session(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        resuming: bool,
+        timeout: Duration,
+    ) -> Result<Session>;
+
+    /// Applies a set of changes to a guild's player on a given Lavalink v4
+    /// node.
+    fn update_player(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        update: &PlayerUpdateRequest,
+    ) -> Result<Player>;
+
+    /// Searches for a query via a given node's LavaSearch plugin.
+    #[cfg(feature = "plugin-lavasearch")]
+    fn search_advanced(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        query: impl AsRef<str>,
+        types: impl IntoIterator<Item = SearchType>,
+    ) -> Result<SearchResult>;
+
+    /// Retrieves the lyrics of an encoded track via a given node's
+    /// LavaLyrics plugin, returning `None` if none were found.
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        track: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Result<Option<Lyrics>>;
+
+    /// Retrieves the lyrics of a guild player's current track via a given
+    /// node's LavaLyrics plugin, returning `None` if none were found.
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn player_lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Result<Option<Lyrics>>;
+
+    /// Subscribes or unsubscribes a guild's player to live lyrics events via
+    /// a given node's LavaLyrics plugin.
+    #[cfg(feature = "plugin-lavalyrics")]
+    fn set_lyrics_subscription(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        subscribed: bool,
+    ) -> Result<()>;
 }
 
-impl LavalinkRestRequester for ReqwestClient {
+mod private {
+    use reqwest::{Method, Request, RequestBuilder, Response, Result};
+
+    /// A client that requests are sent to nodes with.
+    ///
+    /// This is implemented for Reqwest's `Client`, and can't be implemented
+    /// outside of the library.
+    pub trait SendRequest {
+        /// Starts building a request.
+        fn request(&self, method: Method, url: &str) -> RequestBuilder;
+
+        /// Sends a request, returning the response.
+        fn execute(&self, request: Request) -> Result<Response>;
+    }
+}
+
+use self::private::SendRequest;
+
+impl SendRequest for ReqwestClient {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.request(method, url)
+    }
+
+    fn execute(&self, request: Request) -> reqwest::Result<Response> {
+        self.execute(request)
+    }
+}
+
+/// A client that adds the ID of the request to every request.
+struct WithRequestId<'a> {
+    client: &'a ReqwestClient,
+    request_id: usize,
+}
+
+impl<'a> SendRequest for WithRequestId<'a> {
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .header(metrics::REQUEST_ID_HEADER, self.request_id)
+    }
+
+    fn execute(&self, request: Request) -> reqwest::Result<Response> {
+        self.client.execute(request)
+    }
+}
+
+impl<S: SendRequest> LavalinkRestRequester for S {
     #[inline]
     fn load_tracks(
         &self,
@@ -120,6 +826,24 @@ impl LavalinkRestRequester for ReqwestClient {
             host.as_ref(),
             password.as_ref(),
             identifier.as_ref(),
+            &[],
+        )
+    }
+
+    #[inline]
+    fn load_tracks_with(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        identifier: impl AsRef<str>,
+        params: &[(&str, &str)],
+    ) -> Result<Load> {
+        load_tracks(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            identifier.as_ref(),
+            params,
         )
     }
 
@@ -152,14 +876,164 @@ impl LavalinkRestRequester for ReqwestClient {
             &tracks.into_iter().map(Into::into).collect::<Vec<_>>(),
         )
     }
+
+    #[inline]
+    fn version(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<NodeVersion> {
+        version(self, host.as_ref(), password.as_ref())
+    }
+
+    #[inline]
+    fn info(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<NodeInfo> {
+        info(self, host.as_ref(), password.as_ref())
+    }
+
+    #[inline]
+    fn route_planner_status(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+    ) -> Result<Option<RoutePlannerStatus>> {
+        route_planner_status(self, host.as_ref(), password.as_ref())
+    }
+
+    #[inline]
+    fn update_session(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        resuming: bool,
+        timeout: Duration,
+    ) -> Result<Session> {
+        update_session(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            session_id.as_ref(),
+            &Session::new(resuming, timeout),
+        )
+    }
+
+    #[inline]
+    fn update_player(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        update: &PlayerUpdateRequest,
+    ) -> Result<Player> {
+        update_player(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            session_id.as_ref(),
+            guild_id.as_ref(),
+            update,
+        )
+    }
+
+    #[cfg(feature = "plugin-lavasearch")]
+    #[inline]
+    fn search_advanced(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        query: impl AsRef<str>,
+        types: impl IntoIterator<Item = SearchType>,
+    ) -> Result<SearchResult> {
+        search_advanced(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            query.as_ref(),
+            &types.into_iter().collect::<Vec<_>>(),
+        )
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[inline]
+    fn lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        track: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Result<Option<Lyrics>> {
+        let uri = lavalyrics::lyrics_uri(track.as_ref(), skip_track_source);
+
+        lyrics(self, host.as_ref(), password.as_ref(), &uri)
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[inline]
+    fn player_lyrics(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        skip_track_source: bool,
+    ) -> Result<Option<Lyrics>> {
+        let uri = lavalyrics::player_lyrics_uri(
+            session_id.as_ref(),
+            guild_id.as_ref(),
+            skip_track_source,
+        );
+
+        lyrics(self, host.as_ref(), password.as_ref(), &uri)
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[inline]
+    fn set_lyrics_subscription(
+        &self,
+        host: impl AsRef<str>,
+        password: impl AsRef<[u8]>,
+        session_id: impl AsRef<str>,
+        guild_id: impl AsRef<str>,
+        subscribed: bool,
+    ) -> Result<()> {
+        set_lyrics_subscription(
+            self,
+            host.as_ref(),
+            password.as_ref(),
+            session_id.as_ref(),
+            guild_id.as_ref(),
+            subscribed,
+        )
+    }
 }
 
 fn decode_track(
-    client: &ReqwestClient,
+    client: &impl SendRequest,
     host: &str,
     password: &[u8],
     track: String,
 ) -> Result<LoadedTrack> {
+    if track.len() > DECODE_TRACK_QUERY_LIMIT {
+        let request = create_request(
+            client,
+            Method::POST,
+            "/decodetracks",
+            Some(decode_single_track_body(&track)?),
+            host,
+            password,
+        )?.build()?;
+
+        let response = run_request(client, request)?;
+
+        return take_single_track(serde_json::from_slice(&response)?);
+    }
+
     let uri = format!("/decodetrack?track={}", track);
     let request = create_request(
         client,
@@ -172,16 +1046,13 @@ fn decode_track(
 
     let response = run_request(client, request)?;
 
-    let info = serde_json::from_slice(&response)?;
+    let decoded = serde_json::from_slice::<DecodeTrackResponse>(&response)?;
 
-    Ok(LoadedTrack {
-        info,
-        track,
-    })
+    Ok(decoded.into_loaded_track(track))
 }
 
 fn decode_tracks(
-    client: &ReqwestClient,
+    client: &impl SendRequest,
     host: &str,
     password: &[u8],
     tracks: &[Vec<u8>],
@@ -199,22 +1070,16 @@ fn decode_tracks(
 
     run_request(client, request)
         .and_then(|resp| serde_json::from_slice(&resp).map_err(From::from))
-        .map_err(From::from)
 }
 
 fn load_tracks(
-    client: &ReqwestClient,
+    client: &impl SendRequest,
     host: &str,
     password: &[u8],
     identifier: &str,
+    params: &[(&str, &str)],
 ) -> Result<Load> {
-    // url encoding the identifier
-    let identifier = percent_encoding::utf8_percent_encode(
-        identifier,
-        DEFAULT_ENCODE_SET,
-    );
-
-    let uri = format!("/loadtracks?identifier={}", identifier);
+    let uri = super::load_tracks_uri(identifier, params);
     let request = create_request(
         client,
         Method::GET,
@@ -226,11 +1091,182 @@ fn load_tracks(
 
     run_request(client, request)
         .and_then(|body| serde_json::from_slice(&body).map_err(From::from))
-        .map_err(From::from)
 }
 
-fn create_request<'a>(
-    client: &'a ReqwestClient,
+fn version(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+) -> Result<NodeVersion> {
+    let request = create_request(
+        client,
+        Method::GET,
+        "/version",
+        None,
+        host,
+        password,
+    )?.build()?;
+
+    let body = run_request(client, request)?;
+
+    String::from_utf8(body)?.parse()
+}
+
+fn info(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+) -> Result<NodeInfo> {
+    let request = create_request(
+        client,
+        Method::GET,
+        "/v4/info",
+        None,
+        host,
+        password,
+    )?.build()?;
+
+    run_request(client, request)
+        .and_then(|body| serde_json::from_slice(&body).map_err(From::from))
+}
+
+fn route_planner_status(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+) -> Result<Option<RoutePlannerStatus>> {
+    let request = create_request(
+        client,
+        Method::GET,
+        route_planner::STATUS_URI,
+        None,
+        host,
+        password,
+    )?.build()?;
+
+    route_planner::parse_status(&run_request(client, request)?)
+}
+
+fn update_session(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+    session_id: &str,
+    session: &Session,
+) -> Result<Session> {
+    let body = serde_json::to_vec(session)?;
+    let uri = Session::uri(session_id);
+
+    let request = create_request(
+        client,
+        Method::PATCH,
+        uri.as_ref(),
+        Some(body),
+        host,
+        password,
+    )?.build()?;
+
+    run_request(client, request)
+        .and_then(|body| serde_json::from_slice(&body).map_err(From::from))
+}
+
+fn update_player(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+    session_id: &str,
+    guild_id: &str,
+    update: &PlayerUpdateRequest,
+) -> Result<Player> {
+    let body = serde_json::to_vec(update)?;
+    let uri = update.uri(session_id, guild_id);
+
+    let request = create_request(
+        client,
+        Method::PATCH,
+        uri.as_ref(),
+        Some(body),
+        host,
+        password,
+    )?.build()?;
+
+    run_request(client, request)
+        .and_then(|body| serde_json::from_slice(&body).map_err(From::from))
+}
+
+#[cfg(feature = "plugin-lavasearch")]
+fn search_advanced(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+    query: &str,
+    types: &[SearchType],
+) -> Result<SearchResult> {
+    let uri = lavasearch::search_uri(query, types);
+    let request = create_request(
+        client,
+        Method::GET,
+        uri.as_ref(),
+        None,
+        host,
+        password,
+    )?.build()?;
+
+    let (status, body) = run_request_with_status(client, request)?;
+
+    lavasearch::parse_search(status, body)
+}
+
+#[cfg(feature = "plugin-lavalyrics")]
+fn lyrics(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+    uri: &str,
+) -> Result<Option<Lyrics>> {
+    let request = create_request(
+        client,
+        Method::GET,
+        uri,
+        None,
+        host,
+        password,
+    )?.build()?;
+
+    let (status, body) = run_request_with_status(client, request)?;
+
+    lavalyrics::parse_lyrics(status, body)
+}
+
+#[cfg(feature = "plugin-lavalyrics")]
+fn set_lyrics_subscription(
+    client: &impl SendRequest,
+    host: &str,
+    password: &[u8],
+    session_id: &str,
+    guild_id: &str,
+    subscribed: bool,
+) -> Result<()> {
+    let method = if subscribed {
+        Method::POST
+    } else {
+        Method::DELETE
+    };
+    let uri = lavalyrics::subscription_uri(session_id, guild_id);
+    let request = create_request(
+        client,
+        method,
+        uri.as_ref(),
+        None,
+        host,
+        password,
+    )?.build()?;
+
+    run_request(client, request).map(|_| ())
+}
+
+fn create_request(
+    client: &impl SendRequest,
     method: Method,
     uri: &str,
     body: Option<Vec<u8>>,
@@ -256,21 +1292,85 @@ fn create_request<'a>(
     Ok(builder)
 }
 
-fn run_request(client: &ReqwestClient, request: Request) -> Result<Vec<u8>> {
-    match client.execute(request) {
-        Ok(response) => {
-            Ok(response.bytes().fold(Vec::new(), |mut v: Vec<u8>, chunk| {
-                match chunk {
-                    Ok(b) => v.push(b), // append the byte to the vec
-                    Err(e) => {
-                        error!("error parsing response body chunk {:?}", e);
-                        return v;
-                    },
-                };
-
-                v // return the vec as the final result
-            }))
-        },
-        Err(e) => Err(From::from(e)),
+/// Runs a request, returning its body, or an [`Error::Status`] if the node
+/// responded with an unsuccessful status.
+///
+/// [`Error::Status`]: ../../enum.Error.html#variant.Status
+fn run_request(client: &impl SendRequest, request: Request) -> Result<Vec<u8>> {
+    let (status, body) = run_request_with_status(client, request)?;
+
+    super::check_status(status, body)
+}
+
+/// Runs a request, returning the status and body of the response whether or
+/// not it was successful.
+fn run_request_with_status(
+    client: &impl SendRequest,
+    request: Request,
+) -> Result<(u16, Vec<u8>)> {
+    let mut response = client.execute(request)?;
+    let status = response.status().as_u16();
+
+    let mut body = Vec::new();
+    response.read_to_end(&mut body)?;
+    debug!("Status: {}, body: {}", status, String::from_utf8_lossy(&body));
+
+    Ok((status, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use super::RestClient;
+    use super::super::stub::{self, StubNode};
+
+    #[test]
+    fn test_request_id_header() {
+        let node = StubNode::serve("200 OK", "4.0.0");
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+
+        client.version().unwrap();
+        assert!(node.request().contains("x-request-id: "));
+    }
+
+    #[test]
+    fn test_unsuccessful_status() {
+        let node = StubNode::serve("401 Unauthorized", "Unauthorized");
+        let client = RestClient::new(&node.host, "wrong_password").unwrap();
+
+        match client.version() {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 401),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[test]
+    fn test_lyrics_not_found() {
+        let node = StubNode::serve("404 Not Found", "Lyrics not found");
+        let client = RestClient::new(&node.host, "test_password").unwrap();
+
+        let lyrics = client.lyrics("QAAAjQIAJVJpY2sgQXN0bGV5", false);
+        assert!(lyrics.unwrap().is_none());
+    }
+
+    #[cfg(feature = "plugin-lavalyrics")]
+    #[test]
+    fn test_lyrics_subscription_status() {
+        let node = StubNode::serve("404 Not Found", "Player not found");
+        let mut client = RestClient::new(&node.host, "test_password").unwrap();
+        client.set_session_id("la3kfsdf5eafe848");
+
+        match client.set_lyrics_subscription("1", true) {
+            Err(Error::Status(ref inner)) => assert_eq!(inner.status, 404),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reqwest_errors_retryable() {
+        let client = RestClient::new(stub::closed_host(), "test_password").unwrap();
+        let error = client.version().unwrap_err();
+        assert!(error.is_retryable(), "refused connection: {:?}", error);
     }
 }
@@ -0,0 +1,343 @@
+//! Models for the status of a node's route planner, which rotates the IP
+//! addresses that requests to sources such as YouTube are made from.
+//!
+//! The status is retrieved with a REST client's `route_planner_status`
+//! method, which resolves to `None` if the node has no route planner
+//! configured.
+//!
+//! # Examples
+//!
+//! Warn when many of a node's addresses are banned:
+//!
+//! ```rust
+//! use lavalink::rest::route_planner::RoutePlannerStatus;
+//! use serde_json;
+//!
+//! let status = serde_json::from_str::<RoutePlannerStatus>(r#"{
+//!   "class": "RotatingNanoIpRoutePlanner",
+//!   "details": {
+//!     "ipBlock": { "type": "Inet6Address", "size": "18446744073709551616" },
+//!     "failingAddresses": [{
+//!       "failingAddress": "2001:db8::1",
+//!       "failingTimestamp": 1573520707545,
+//!       "failingTime": "Mon Nov 11 20:05:07 EST 2019"
+//!     }],
+//!     "blockIndex": "0",
+//!     "currentAddressIndex": "36792023813"
+//!   }
+//! }"#)?;
+//!
+//! assert_eq!(status.class(), "RotatingNanoIpRoutePlanner");
+//!
+//! if status.failing_addresses().len() > 100 {
+//!     eprintln!("Many addresses of the node are banned");
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use serde::de::{Deserializer, Error as DeError, Unexpected, Visitor};
+use std::fmt::{Formatter, Result as FmtResult};
+use std::result::Result as StdResult;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use crate::Result;
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+use serde_json::{self, Value};
+
+/// The status of a node's route planner, by the class of the planner.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(content = "details", tag = "class")]
+pub enum RoutePlannerStatus {
+    /// A planner that picks a random address for each request.
+    #[serde(rename = "BalancingIpRoutePlanner")]
+    Balancing(BalancingIpDetails),
+    /// A planner that switches to the next address for each request.
+    #[serde(rename = "NanoIpRoutePlanner")]
+    Nano(NanoIpDetails),
+    /// A planner that switches to the next address once the current one is
+    /// banned.
+    #[serde(rename = "RotatingIpRoutePlanner")]
+    Rotating(RotatingIpDetails),
+    /// A planner that switches to the next address for each request, and to
+    /// the next /64 block once the current one is banned.
+    #[serde(rename = "RotatingNanoIpRoutePlanner")]
+    RotatingNano(RotatingNanoIpDetails),
+}
+
+impl RoutePlannerStatus {
+    /// Returns the name of the planner's class, as sent by the node.
+    pub fn class(&self) -> &'static str {
+        match self {
+            RoutePlannerStatus::Balancing(_) => "BalancingIpRoutePlanner",
+            RoutePlannerStatus::Nano(_) => "NanoIpRoutePlanner",
+            RoutePlannerStatus::Rotating(_) => "RotatingIpRoutePlanner",
+            RoutePlannerStatus::RotatingNano(_) => "RotatingNanoIpRoutePlanner",
+        }
+    }
+
+    /// Returns the block of addresses the planner uses.
+    pub fn ip_block(&self) -> &IpBlock {
+        match self {
+            RoutePlannerStatus::Balancing(details) => &details.ip_block,
+            RoutePlannerStatus::Nano(details) => &details.ip_block,
+            RoutePlannerStatus::Rotating(details) => &details.ip_block,
+            RoutePlannerStatus::RotatingNano(details) => &details.ip_block,
+        }
+    }
+
+    /// Returns the addresses that are currently marked as failing, such as
+    /// by being banned by a source.
+    pub fn failing_addresses(&self) -> &[FailingAddress] {
+        match self {
+            RoutePlannerStatus::Balancing(details) => &details.failing_addresses,
+            RoutePlannerStatus::Nano(details) => &details.failing_addresses,
+            RoutePlannerStatus::Rotating(details) => &details.failing_addresses,
+            RoutePlannerStatus::RotatingNano(details) => {
+                &details.failing_addresses
+            },
+        }
+    }
+}
+
+/// The details of a [`RoutePlannerStatus::Balancing`] planner.
+///
+/// [`RoutePlannerStatus::Balancing`]: enum.RoutePlannerStatus.html#variant.Balancing
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancingIpDetails {
+    /// The block of addresses the planner uses.
+    pub ip_block: IpBlock,
+    /// The addresses that are currently marked as failing.
+    #[serde(default)]
+    pub failing_addresses: Vec<FailingAddress>,
+}
+
+/// The details of a [`RoutePlannerStatus::Nano`] planner.
+///
+/// [`RoutePlannerStatus::Nano`]: enum.RoutePlannerStatus.html#variant.Nano
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NanoIpDetails {
+    /// The block of addresses the planner uses.
+    pub ip_block: IpBlock,
+    /// The addresses that are currently marked as failing.
+    #[serde(default)]
+    pub failing_addresses: Vec<FailingAddress>,
+    /// The index of the current address in the block.
+    #[serde(deserialize_with = "deserialize_big_number")]
+    pub current_address_index: String,
+}
+
+/// The details of a [`RoutePlannerStatus::Rotating`] planner.
+///
+/// [`RoutePlannerStatus::Rotating`]: enum.RoutePlannerStatus.html#variant.Rotating
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotatingIpDetails {
+    /// The block of addresses the planner uses.
+    pub ip_block: IpBlock,
+    /// The addresses that are currently marked as failing.
+    #[serde(default)]
+    pub failing_addresses: Vec<FailingAddress>,
+    /// The number of times the planner has rotated to another address.
+    #[serde(deserialize_with = "deserialize_big_number")]
+    pub rotate_index: String,
+    /// The index of the current address in the block.
+    #[serde(deserialize_with = "deserialize_big_number")]
+    pub ip_index: String,
+    /// The current address.
+    pub current_address: String,
+}
+
+/// The details of a [`RoutePlannerStatus::RotatingNano`] planner.
+///
+/// [`RoutePlannerStatus::RotatingNano`]: enum.RoutePlannerStatus.html#variant.RotatingNano
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RotatingNanoIpDetails {
+    /// The block of addresses the planner uses.
+    pub ip_block: IpBlock,
+    /// The addresses that are currently marked as failing.
+    #[serde(default)]
+    pub failing_addresses: Vec<FailingAddress>,
+    /// The index of the current /64 block.
+    #[serde(deserialize_with = "deserialize_big_number")]
+    pub block_index: String,
+    /// The index of the current address in the block.
+    #[serde(deserialize_with = "deserialize_big_number")]
+    pub current_address_index: String,
+}
+
+/// A block of addresses used by a route planner.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct IpBlock {
+    /// The type of the addresses.
+    #[serde(rename = "type")]
+    pub kind: IpBlockType,
+    /// The number of addresses in the block.
+    ///
+    /// This is a string, as IPv6 blocks can be larger than a `u64`.
+    #[serde(deserialize_with = "deserialize_big_number")]
+    pub size: String,
+}
+
+/// The type of the addresses in an [`IpBlock`].
+///
+/// [`IpBlock`]: struct.IpBlock.html
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum IpBlockType {
+    /// IPv4 addresses.
+    #[serde(rename = "Inet4Address")]
+    Ipv4,
+    /// IPv6 addresses.
+    #[serde(rename = "Inet6Address")]
+    Ipv6,
+}
+
+/// An address that a route planner marked as failing.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailingAddress {
+    /// The address.
+    ///
+    /// Lavalink v3 nodes name this field `address`.
+    #[serde(alias = "address", rename = "failingAddress")]
+    pub address: String,
+    /// The Unix timestamp in milliseconds at which the address failed.
+    pub failing_timestamp: u64,
+    /// The time at which the address failed, formatted by the node.
+    pub failing_time: String,
+}
+
+impl FailingAddress {
+    /// Returns the time at which the address failed.
+    pub fn failed_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.failing_timestamp)
+    }
+}
+
+/// The URI path of the route planner status endpoint.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) const STATUS_URI: &str = "/routeplanner/status";
+
+/// Parses the body of a route planner status response.
+///
+/// Nodes without a route planner respond with no content, or with a `null`
+/// class on older versions.
+#[cfg(any(feature = "hyper", feature = "reqwest"))]
+pub(crate) fn parse_status(body: &[u8]) -> Result<Option<RoutePlannerStatus>> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+
+    let value = serde_json::from_slice::<Value>(body)?;
+
+    if value.get("class").map_or(true, Value::is_null) {
+        return Ok(None);
+    }
+
+    serde_json::from_value(value).map(Some).map_err(From::from)
+}
+
+// Nodes have sent indexes both as numbers and as strings, and the numbers can
+// be too large for a u64, so both are read as strings.
+fn deserialize_big_number<'de, D>(deserializer: D) -> StdResult<String, D::Error>
+    where D: Deserializer<'de> {
+    struct BigNumberVisitor;
+
+    impl<'de> Visitor<'de> for BigNumberVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut Formatter) -> FmtResult {
+            f.write_str("an integer, or a string of an integer")
+        }
+
+        fn visit_u64<E: DeError>(self, v: u64) -> StdResult<String, E> {
+            Ok(v.to_string())
+        }
+
+        fn visit_i64<E: DeError>(self, v: i64) -> StdResult<String, E> {
+            Ok(v.to_string())
+        }
+
+        fn visit_str<E: DeError>(self, v: &str) -> StdResult<String, E> {
+            if v.is_empty() || !v.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(E::invalid_value(Unexpected::Str(v), &self));
+            }
+
+            Ok(v.to_owned())
+        }
+    }
+
+    deserializer.deserialize_any(BigNumberVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use super::*;
+
+    static ROTATING: &str = r#"{
+  "class": "RotatingIpRoutePlanner",
+  "details": {
+    "ipBlock": {
+      "type": "Inet6Address",
+      "size": "1208925819614629174706176"
+    },
+    "failingAddresses": [
+      {
+        "address": "/1.0.0.0",
+        "failingTimestamp": 1573520707545,
+        "failingTime": "Mon Nov 11 20:05:07 EST 2019"
+      }
+    ],
+    "rotateIndex": "0",
+    "ipIndex": 0,
+    "currentAddress": "/1.0.0.0"
+  }
+}"#;
+
+    #[test]
+    fn test_status_deser() {
+        let status = serde_json::from_str::<RoutePlannerStatus>(ROTATING)
+            .unwrap();
+        assert_eq!(status.class(), "RotatingIpRoutePlanner");
+        assert_eq!(status.ip_block().kind, IpBlockType::Ipv6);
+        assert_eq!(status.ip_block().size, "1208925819614629174706176");
+
+        let failing = &status.failing_addresses()[0];
+        assert_eq!(failing.address, "/1.0.0.0");
+        assert_eq!(
+            failing.failed_at(),
+            UNIX_EPOCH + Duration::from_millis(1573520707545),
+        );
+
+        match status {
+            RoutePlannerStatus::Rotating(details) => {
+                assert_eq!(details.ip_index, "0");
+            },
+            other => panic!("parsed as {:?}", other),
+        }
+
+        let value = serde_json::from_str::<serde_json::Value>(r#"{
+  "class": "NanoIpRoutePlanner",
+  "details": {
+    "ipBlock": { "type": "Inet4Address", "size": "-1" },
+    "failingAddresses": [],
+    "currentAddressIndex": "1"
+  }
+}"#).unwrap();
+        assert!(serde_json::from_value::<RoutePlannerStatus>(value).is_err());
+    }
+
+    #[cfg(any(feature = "hyper", feature = "reqwest"))]
+    #[test]
+    fn test_parse_status() {
+        assert!(parse_status(b"").unwrap().is_none());
+        assert!(parse_status(br#"{"class":null,"details":null}"#)
+            .unwrap()
+            .is_none());
+        assert!(parse_status(ROTATING.as_bytes()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,157 @@
+//! Stub nodes for testing the REST clients, which answer requests with a
+//! canned response.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+
+#[cfg(feature = "hyper")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "hyper")]
+use std::sync::Arc;
+
+/// A stub node listening on a local port.
+pub(crate) struct StubNode {
+    /// The host of the node, such as `http://127.0.0.1:40000`.
+    pub host: String,
+    request: JoinHandle<String>,
+}
+
+impl StubNode {
+    /// Starts a node that answers the first request with an HTTP status and
+    /// body.
+    pub fn serve(status: &'static str, body: &'static str) -> Self {
+        Self::serve_raw(response(status, body))
+    }
+
+    /// Starts a node that answers the first request with raw bytes, which
+    /// don't need to be a valid response.
+    pub fn serve_raw(response: String) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+
+        let request = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let request = read_request(&mut reader);
+
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+            request
+        });
+
+        Self {
+            host,
+            request,
+        }
+    }
+
+    /// Waits for the request to be answered, returning it as it was
+    /// received.
+    pub fn request(self) -> String {
+        self.request.join().unwrap()
+    }
+}
+
+/// A stub node that answers every request with the same response, counting
+/// the requests.
+#[cfg(feature = "hyper")]
+pub(crate) struct CountingNode {
+    /// The host of the node, such as `http://127.0.0.1:40000`.
+    pub host: String,
+    requests: Arc<AtomicUsize>,
+}
+
+#[cfg(feature = "hyper")]
+impl CountingNode {
+    /// Starts a node that answers every request with an HTTP status and body.
+    pub fn serve(status: &'static str, body: &'static str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&requests);
+
+        // the thread is left running, as the listener is never closed
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                read_request(&mut reader);
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let response = response(status, body);
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        Self {
+            host,
+            requests,
+        }
+    }
+
+    /// Returns the number of requests that were received.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+fn response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body,
+    )
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> String {
+    let mut request = String::new();
+    let mut content_length = 0;
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+
+        if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+            content_length = length.trim().parse().unwrap();
+        }
+
+        request.push_str(&line);
+
+        if line == "\r\n" || line.is_empty() {
+            break;
+        }
+    }
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content).unwrap();
+    request.push_str(&String::from_utf8_lossy(&content));
+
+    request
+}
+
+/// Returns the host of a local port that nothing listens on.
+pub(crate) fn closed_host() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+/// Runs a future to completion on a Tokio runtime.
+#[cfg(feature = "hyper")]
+pub(crate) fn block_on<T, E>(
+    future: impl futures::Future<Item = T, Error = E> + Send + 'static,
+) -> Result<T, E>
+    where T: Send + 'static,
+          E: Send + 'static {
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    hyper::rt::run(future.then(move |result| {
+        tx.send(result).unwrap();
+
+        Ok(())
+    }));
+
+    rx.recv().unwrap()
+}
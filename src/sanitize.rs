@@ -0,0 +1,150 @@
+//! Helpers for cleaning up track metadata before displaying it, such as in
+//! Discord embeds.
+//!
+//! Titles and authors come from the track's source as they were uploaded, and
+//! often contain characters that break the layout of a message: bidirectional
+//! overrides that reverse the surrounding text, invisible zero-width
+//! characters, line breaks, or characters that Discord treats as markdown.
+
+/// Which changes [`sanitize`] makes to a string.
+///
+/// The default policy enables all of them.
+///
+/// [`sanitize`]: fn.sanitize.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SanitizePolicy {
+    /// Whether to remove bidirectional formatting characters, such as the
+    /// right-to-left override.
+    pub strip_bidi: bool,
+    /// Whether to remove control characters. Line breaks and tabs are
+    /// replaced with a space, and leading and trailing whitespace is trimmed.
+    pub strip_control: bool,
+    /// Whether to remove zero-width characters, such as zero-width spaces
+    /// and byte order marks.
+    pub strip_zero_width: bool,
+    /// Whether to escape characters that Discord treats as markdown with a
+    /// backslash.
+    pub escape_markdown: bool,
+}
+
+impl SanitizePolicy {
+    /// Creates a policy that removes unwanted characters but doesn't escape
+    /// markdown, for text that isn't displayed in a Discord message.
+    pub fn plain() -> Self {
+        Self {
+            escape_markdown: false,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self {
+            escape_markdown: true,
+            strip_bidi: true,
+            strip_control: true,
+            strip_zero_width: true,
+        }
+    }
+}
+
+/// Cleans up a string according to a policy.
+///
+/// Unicode normalization is not performed.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::sanitize::{sanitize, SanitizePolicy};
+///
+/// let title = "\u{202e}**Never** Gonna\u{200b}\nGive You Up";
+///
+/// assert_eq!(
+///     sanitize(title, &SanitizePolicy::default()),
+///     r"\*\*Never\*\* Gonna Give You Up",
+/// );
+/// assert_eq!(
+///     sanitize(title, &SanitizePolicy::plain()),
+///     "**Never** Gonna Give You Up",
+/// );
+/// ```
+#[inline]
+pub fn sanitize(text: impl AsRef<str>, policy: &SanitizePolicy) -> String {
+    _sanitize(text.as_ref(), policy)
+}
+
+fn _sanitize(text: &str, policy: &SanitizePolicy) -> String {
+    let mut sanitized = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if policy.strip_bidi && is_bidi(c) {
+            continue;
+        }
+        if policy.strip_zero_width && is_zero_width(c) {
+            continue;
+        }
+        if policy.strip_control && c.is_control() {
+            if c.is_whitespace() {
+                sanitized.push(' ');
+            }
+
+            continue;
+        }
+        if policy.escape_markdown && is_markdown(c) {
+            sanitized.push('\\');
+        }
+
+        sanitized.push(c);
+    }
+
+    if policy.strip_control {
+        let trimmed = sanitized.trim();
+
+        if trimmed.len() != sanitized.len() {
+            return trimmed.to_owned();
+        }
+    }
+
+    sanitized
+}
+
+fn is_bidi(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061c}'
+            | '\u{200e}'
+            | '\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2066}'..='\u{2069}'
+    )
+}
+
+fn is_markdown(c: char) -> bool {
+    matches!(c, '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '[' | ']' | '#')
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(c, '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize, SanitizePolicy};
+
+    #[test]
+    fn test_policies() {
+        let text = "\t\u{feff}a_b\u{2067}c\u{7}\r\n";
+
+        assert_eq!(sanitize(text, &SanitizePolicy::default()), r"a\_bc");
+        assert_eq!(sanitize(text, &SanitizePolicy::plain()), "a_bc");
+
+        let policy = SanitizePolicy {
+            escape_markdown: false,
+            strip_bidi: false,
+            strip_control: false,
+            strip_zero_width: false,
+        };
+        assert_eq!(sanitize(text, &policy), text);
+    }
+}
@@ -0,0 +1,82 @@
+//! A wrapper for sensitive values such as node passwords.
+
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+/// A sensitive value, such as the password of a node.
+///
+/// The `Debug` implementation never prints the value itself, so a `Secret`
+/// can be included in types deriving `Debug` without leaking it into logs.
+///
+/// With the `zeroize` feature enabled, the value is overwritten with zeroes
+/// when the `Secret` is dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::Secret;
+///
+/// let password = Secret::new("youshallnotpass");
+///
+/// assert_eq!(password.expose(), b"youshallnotpass");
+/// assert_eq!(format!("{:?}", password), "Secret([redacted])");
+/// ```
+#[derive(Clone, Eq, PartialEq)]
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+    /// Creates a new secret from a value.
+    #[inline]
+    pub fn new(value: impl Into<Vec<u8>>) -> Self {
+        Secret(value.into())
+    }
+
+    /// Returns the value of the secret.
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Secret {
+    fn as_ref(&self) -> &[u8] {
+        self.expose()
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("Secret([redacted])")
+    }
+}
+
+impl From<Vec<u8>> for Secret {
+    fn from(value: Vec<u8>) -> Self {
+        Secret(value)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret(value.into_bytes())
+    }
+}
+
+impl<'a> From<&'a str> for Secret {
+    fn from(value: &'a str) -> Self {
+        Secret(value.into())
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Secret {
+    fn drop(&mut self) {
+        use std::sync::atomic::{self, Ordering};
+
+        for byte in self.0.iter_mut() {
+            // Volatile writes so that the compiler can't optimize away stores
+            // to memory that is about to be freed.
+            unsafe { ::std::ptr::write_volatile(byte, 0) };
+        }
+
+        atomic::compiler_fence(Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,40 @@
+//! Utilities for working with Discord's sharding.
+//!
+//! A node sends voice updates for a guild through the shard that the guild
+//! belongs to, based on the `Num-Shards` value given when connecting to it.
+
+/// Returns the ID of the shard that a guild belongs to.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::sharding;
+///
+/// assert_eq!(sharding::shard_for(381880193251409931, 10), 2);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `num_shards` is `0`.
+pub fn shard_for(guild_id: u64, num_shards: u64) -> u64 {
+    (guild_id >> 22) % num_shards
+}
+
+/// Returns whether a guild belongs to the given shard.
+///
+/// This returns `false` if `num_shards` is `0` or the shard ID is out of
+/// range, which indicates that the shard information given to the node is
+/// not valid.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::sharding;
+///
+/// assert!(sharding::is_shard_for(381880193251409931, 2, 10));
+/// assert!(!sharding::is_shard_for(381880193251409931, 0, 10));
+/// assert!(!sharding::is_shard_for(381880193251409931, 2, 0));
+/// ```
+pub fn is_shard_for(guild_id: u64, shard_id: u64, num_shards: u64) -> bool {
+    num_shards != 0 && shard_for(guild_id, num_shards) == shard_id
+}
@@ -0,0 +1,79 @@
+//! Utilities for validating Discord snowflakes, such as guild IDs.
+//!
+//! Payloads take guild IDs as strings, and a node silently ignores commands
+//! for a guild ID it can't make sense of. Parsing user-provided IDs with
+//! [`parse`] before constructing a payload surfaces such mistakes early.
+//!
+//! [`parse`]: fn.parse.html
+
+use crate::{Error, Result};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The Discord epoch, the first millisecond of 2015, as a Unix timestamp in
+/// milliseconds.
+pub const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+/// Parses and validates a snowflake.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::snowflake;
+///
+/// assert_eq!(snowflake::parse("381880193251409931").unwrap(), 381880193251409931);
+/// assert!(snowflake::parse("not a guild").is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidGuildId`] if the value is not a non-zero integer
+/// consisting only of ASCII digits.
+///
+/// [`Error::InvalidGuildId`]: ../enum.Error.html#variant.InvalidGuildId
+#[inline]
+pub fn parse(id: impl AsRef<str>) -> Result<u64> {
+    _parse(id.as_ref())
+}
+
+fn _parse(id: &str) -> Result<u64> {
+    if id.is_empty() || !id.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(Error::InvalidGuildId(id.to_owned()));
+    }
+
+    match id.parse() {
+        Ok(0) | Err(_) => Err(Error::InvalidGuildId(id.to_owned())),
+        Ok(id) => Ok(id),
+    }
+}
+
+/// Returns the time at which the snowflake was created.
+///
+/// # Examples
+///
+/// ```rust
+/// use lavalink::snowflake;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let created_at = snowflake::created_at(381880193251409931);
+/// let elapsed = created_at.duration_since(UNIX_EPOCH).unwrap();
+/// assert_eq!(elapsed, Duration::from_millis(1_511_117_733_062));
+/// ```
+pub fn created_at(id: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis((id >> 22) + DISCORD_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("1").unwrap(), 1);
+        assert!(parse("").is_err());
+        assert!(parse("0").is_err());
+        assert!(parse("+1").is_err());
+        assert!(parse("-1").is_err());
+        assert!(parse(" 1").is_err());
+        assert!(parse("99999999999999999999").is_err());
+    }
+}
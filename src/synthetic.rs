@@ -0,0 +1,198 @@
+//! Generators of plausible node payloads, for simulating nodes in tests of
+//! code such as load balancers.
+//!
+//! The generators are deterministic: the same seed always produces the same
+//! payloads, so a simulation that fails can be reproduced.
+
+use crate::model::{Stats, StatsCpu, StatsFrames, StatsMemory};
+
+/// The number of milliseconds between the stats that a node sends.
+const STATS_INTERVAL_MS: i64 = 60_000;
+
+/// The memory used by a node without any players, in bytes.
+const BASE_MEMORY: i64 = 96 * 1024 * 1024;
+
+/// The memory used by each player, in bytes.
+const PLAYER_MEMORY: i64 = 2 * 1024 * 1024;
+
+/// The granularity the JVM allocates memory in, in bytes.
+const MEMORY_CHUNK: i64 = 64 * 1024 * 1024;
+
+/// The number of frames a player is expected to send each minute.
+const FRAMES_PER_MINUTE: i64 = 3000;
+
+/// Creates an endless sequence of stats from a simulated node.
+///
+/// Each payload is one minute after the previous one, like those sent by a
+/// real node. Players are gradually created and destroyed, and the CPU load,
+/// memory usage and frame loss follow the number of players.
+///
+/// # Examples
+///
+/// Take an hour of stats:
+///
+/// ```rust
+/// use lavalink::synthetic;
+///
+/// let stats = synthetic::stats_sequence(7).take(60).collect::<Vec<_>>();
+///
+/// assert_eq!(stats.len(), 60);
+/// assert!(stats.iter().all(|stats| stats.playing_players <= stats.players));
+/// ```
+pub fn stats_sequence(seed: u64) -> StatsSequence {
+    StatsSequence::new(seed)
+}
+
+/// An endless iterator of simulated node stats.
+///
+/// This is created with [`stats_sequence`].
+///
+/// [`stats_sequence`]: fn.stats_sequence.html
+#[derive(Clone, Debug)]
+pub struct StatsSequence {
+    cores: i64,
+    max_players: i32,
+    players: i32,
+    playing_players: i32,
+    rng: u64,
+    uptime: i64,
+}
+
+impl StatsSequence {
+    fn new(seed: u64) -> Self {
+        let mut sequence = Self {
+            cores: 4,
+            max_players: 1000,
+            players: 0,
+            playing_players: 0,
+            // xorshift gets stuck on a state of zero
+            rng: (seed ^ 0x9E37_79B9_7F4A_7C15).max(1),
+            uptime: 0,
+        };
+        sequence.players = sequence.range(0, 50) as i32;
+
+        sequence
+    }
+
+    /// Sets the number of CPU cores of the simulated node.
+    ///
+    /// Defaults to 4.
+    pub fn cores(mut self, cores: i64) -> Self {
+        self.cores = cores.max(1);
+
+        self
+    }
+
+    /// Sets the most players the simulated node will have at once.
+    ///
+    /// Defaults to 1000.
+    pub fn max_players(mut self, max_players: i32) -> Self {
+        self.max_players = max_players.max(0);
+        self.players = self.players.min(self.max_players);
+
+        self
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+
+        self.rng
+    }
+
+    /// Returns a number from `min` up to and including `max`.
+    fn range(&mut self, min: i64, max: i64) -> i64 {
+        min + (self.next_u64() % (max - min + 1) as u64) as i64
+    }
+
+    /// Returns a number from `0.0` up to `1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Iterator for StatsSequence {
+    type Item = Stats;
+
+    fn next(&mut self) -> Option<Stats> {
+        self.uptime += STATS_INTERVAL_MS;
+
+        let players = i64::from(self.players) + self.range(-5, 5);
+        self.players = players.max(0).min(i64::from(self.max_players)) as i32;
+        let players = i64::from(self.players);
+        self.playing_players = (players - self.range(0, players / 5)) as i32;
+        let playing = i64::from(self.playing_players);
+
+        let capacity = self.cores as f64 * 250.0;
+        let lavalink_load = (playing as f64 / capacity + self.unit() * 0.02)
+            .min(1.0);
+        let system_load = (lavalink_load + self.unit() * 0.1).min(1.0);
+
+        let used = BASE_MEMORY + players * PLAYER_MEMORY
+            + self.range(0, PLAYER_MEMORY);
+        let allocated = (used / MEMORY_CHUNK + 1) * MEMORY_CHUNK;
+
+        // nodes only send frame stats while players are playing
+        let frames = if playing == 0 {
+            None
+        } else {
+            // frames are mostly lost once the node is close to fully loaded
+            let loss = lavalink_load * lavalink_load * self.unit() * 0.2;
+            let lost = (FRAMES_PER_MINUTE as f64 * loss) as i64;
+            let nulled = self.range(0, lost);
+
+            Some(StatsFrames::new(FRAMES_PER_MINUTE - lost, nulled, lost - nulled))
+        };
+
+        Some(Stats::builder()
+            .cpu(StatsCpu::new(self.cores, system_load, lavalink_load))
+            .frames(frames)
+            .memory(StatsMemory::new(
+                allocated,
+                allocated - used,
+                4 * 1024 * 1024 * 1024,
+                used,
+            ))
+            .players(self.players)
+            .playing_players(self.playing_players)
+            .uptime(self.uptime)
+            .build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stats_sequence;
+
+    #[test]
+    fn test_stats_sequence() {
+        let first = stats_sequence(1).take(100).collect::<Vec<_>>();
+        let second = stats_sequence(1).take(100).collect::<Vec<_>>();
+
+        for (index, (a, b)) in first.iter().zip(&second).enumerate() {
+            assert_eq!(a.players, b.players);
+            assert_eq!(a.memory.used, b.memory.used);
+            assert_eq!(a.uptime, (index as i64 + 1) * 60_000);
+
+            assert!(a.playing_players >= 0);
+            assert!(a.playing_players <= a.players);
+            assert!(a.players <= 1000);
+            assert!(a.cpu.lavalink_load <= a.cpu.system_load);
+            assert!(a.cpu.system_load <= 1.0);
+            assert!(a.memory.free >= 0);
+
+            match a.frames {
+                Some(ref frames) => {
+                    assert_eq!(frames.expected_frame_count(), 3000);
+                },
+                None => assert_eq!(a.playing_players, 0),
+            }
+        }
+
+        assert!(stats_sequence(2)
+            .max_players(3)
+            .take(20)
+            .all(|stats| stats.players <= 3));
+    }
+}